@@ -0,0 +1,165 @@
+use crate::texture::Texture;
+use std::f32::consts::TAU;
+use std::fs;
+
+// Minecraft cuenta el tiempo de animación en ticks de 1/20 s
+const TICKS_PER_SECOND: f32 = 20.0;
+
+pub enum Animation {
+    // se regenera con el tiempo en segundos
+    Procedural(fn(f32) -> Texture),
+    // frames de una tira vertical, cada uno con su duración en segundos
+    Frames { frames: Vec<Texture>, sequence: Vec<(usize, f32)> },
+}
+
+pub struct AnimatedTexture {
+    pub animation: Animation,
+    current_frame: Option<usize>,
+}
+
+impl AnimatedTexture {
+    pub fn procedural(generator: fn(f32) -> Texture) -> Self {
+        AnimatedTexture { animation: Animation::Procedural(generator), current_frame: None }
+    }
+
+    // tira vertical estilo Minecraft: frames cuadrados uno debajo del otro,
+    // con `<path>.mcmeta` opcional para frametime y orden de frames
+    pub fn load_strip(path: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let strip = Texture::load_from_file(path)?;
+        if strip.height < strip.width || strip.height % strip.width != 0 {
+            return Err(format!("{} no es una tira vertical de frames cuadrados", path).into());
+        }
+
+        let frame_size = strip.width;
+        let frame_count = (strip.height / frame_size) as usize;
+        let row_bytes = (frame_size * 3) as usize;
+        let frame_bytes = row_bytes * frame_size as usize;
+        let frames: Vec<Texture> = (0..frame_count)
+            .map(|i| Texture {
+                width: frame_size,
+                height: frame_size,
+                data: strip.data[i * frame_bytes..(i + 1) * frame_bytes].to_vec(),
+            })
+            .collect();
+
+        let meta = fs::read_to_string(format!("{}.mcmeta", path)).unwrap_or_default();
+        let sequence = parse_mcmeta(&meta, frame_count);
+
+        Ok(AnimatedTexture { animation: Animation::Frames { frames, sequence }, current_frame: None })
+    }
+
+    // anima una imagen fija: cada frame la corre hacia abajo con una ondulación de lado y un pulso de brillo.
+    // el último frame empalma con el primero, así la vuelta no salta
+    pub fn from_still(base: &Texture, frame_count: usize, frame_time: f32) -> Self {
+        let (width, height) = (base.width as usize, base.height as usize);
+        let frames = (0..frame_count).map(|i| {
+            let phase = i as f32 / frame_count as f32;
+            let mut data = Vec::with_capacity(width * height * 3);
+            for y in 0..height {
+                let sway = ((y as f32 / height as f32 * 2.0 + phase) * TAU).sin() * width as f32 / 32.0;
+                let source_y = ((y as f32 - phase * height as f32).rem_euclid(height as f32) as usize).min(height - 1);
+                for x in 0..width {
+                    let source_x = (x as f32 + sway).round().rem_euclid(width as f32) as usize;
+                    let pulse = 0.9 + 0.1 * ((phase + (x + y) as f32 / width as f32) * TAU).sin();
+                    let start = (source_y * width + source_x) * 3;
+                    data.extend(base.data[start..start + 3].iter().map(|&c| (c as f32 * pulse).min(255.0) as u8));
+                }
+            }
+            Texture { width: base.width, height: base.height, data }
+        }).collect();
+        let sequence = (0..frame_count).map(|i| (i, frame_time)).collect();
+        AnimatedTexture { animation: Animation::Frames { frames, sequence }, current_frame: None }
+    }
+
+    // frame nuevo para este tiempo, o None si sigue siendo el mismo de antes
+    pub fn advance(&mut self, time: f32) -> Option<Texture> {
+        match &self.animation {
+            Animation::Procedural(generator) => Some(generator(time)),
+            Animation::Frames { frames, sequence } => {
+                let total: f32 = sequence.iter().map(|(_, duration)| duration).sum();
+                if total <= 0.0 {
+                    return None;
+                }
+                let mut t = time.rem_euclid(total);
+                let mut frame = sequence[0].0;
+                for &(index, duration) in sequence {
+                    frame = index;
+                    if t < duration {
+                        break;
+                    }
+                    t -= duration;
+                }
+                if self.current_frame == Some(frame) {
+                    return None;
+                }
+                self.current_frame = Some(frame);
+                frames.get(frame).cloned()
+            }
+        }
+    }
+}
+
+// lee lo poco que se usa del .mcmeta: "frametime" y la lista "frames"
+// (números o {"index": i, "time": t}); sin lista, los frames van en orden
+fn parse_mcmeta(meta: &str, frame_count: usize) -> Vec<(usize, f32)> {
+    let default_ticks = number_after(meta, "\"frametime\"").unwrap_or(1.0).max(1.0);
+    let default_time = default_ticks / TICKS_PER_SECOND;
+
+    let mut sequence = Vec::new();
+    if let Some(start) = meta.find("\"frames\"") {
+        let rest = &meta[start..];
+        if let (Some(open), Some(close)) = (rest.find('['), rest.find(']')) {
+            let list = &rest[open + 1..close];
+            for entry in split_entries(list) {
+                let (index, ticks) = if entry.contains('{') {
+                    (number_after(entry, "\"index\""), number_after(entry, "\"time\""))
+                } else {
+                    (entry.trim().parse::<f32>().ok(), None)
+                };
+                if let Some(index) = index {
+                    let index = index as usize;
+                    if index < frame_count {
+                        let time = ticks.map_or(default_time, |t| t.max(1.0) / TICKS_PER_SECOND);
+                        sequence.push((index, time));
+                    }
+                }
+            }
+        }
+    }
+
+    if sequence.is_empty() {
+        sequence = (0..frame_count).map(|i| (i, default_time)).collect();
+    }
+    sequence
+}
+
+// separa por comas de primer nivel, sin cortar los objetos {..}
+fn split_entries(list: &str) -> Vec<&str> {
+    let mut entries = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in list.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => depth -= 1,
+            ',' if depth == 0 => {
+                entries.push(&list[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    if !list[start..].trim().is_empty() {
+        entries.push(&list[start..]);
+    }
+    entries
+}
+
+fn number_after(text: &str, key: &str) -> Option<f32> {
+    let after_key = &text[text.find(key)? + key.len()..];
+    let after_colon = after_key.trim_start().strip_prefix(':')?.trim_start();
+    let end = after_colon
+        .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == '-'))
+        .unwrap_or(after_colon.len());
+    after_colon[..end].parse().ok()
+}
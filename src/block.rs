@@ -0,0 +1,136 @@
+use crate::color::Color;
+use crate::material::Material;
+use std::collections::HashMap;
+
+// índice en el registro; el mundo guarda solo esto por bloque
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct BlockId(pub u16);
+
+impl BlockId {
+    pub const AIR: BlockId = BlockId(0);
+
+    pub fn is_air(self) -> bool {
+        self == BlockId::AIR
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BlockShape {
+    Empty,
+    Full,
+    Small(f32),  // cubo más chico apoyado en el piso (flores)
+    Liquid,      // bloque de fluido que cae: baja hasta el nivel de la superficie
+}
+
+#[derive(Debug, Clone)]
+pub struct BlockDef {
+    pub name: String,
+    pub material: Material,
+    pub shape: BlockShape,
+}
+
+impl BlockDef {
+    // tapa por completo la cara del vecino
+    pub fn is_opaque(&self) -> bool {
+        self.shape == BlockShape::Full && !self.material.is_transparent()
+    }
+}
+
+// los bloques que conoce el mundo; el id 0 siempre es aire
+pub struct BlockRegistry {
+    blocks: Vec<BlockDef>,
+    by_name: HashMap<String, BlockId>,
+}
+
+// flores con nombre y color de pétalos; la vegetación elige entre estas
+pub const FLOWERS: [(&str, [u8; 3]); 4] = [
+    ("poppy", [220, 40, 40]),
+    ("dandelion", [245, 215, 50]),
+    ("cornflower", [110, 120, 235]),
+    ("oxeye_daisy", [240, 240, 240]),
+];
+
+impl BlockRegistry {
+    pub fn empty() -> Self {
+        let mut registry = BlockRegistry { blocks: Vec::new(), by_name: HashMap::new() };
+        registry.register("air", Material::new(Color::black()), BlockShape::Empty);
+        registry
+    }
+
+    // todos los bloques de los presets de materiales
+    pub fn with_defaults() -> Self {
+        let mut registry = Self::empty();
+        let full = [
+            ("grass_block", Material::grass_top()),
+            ("dirt", Material::dirt_layer()),
+            ("stone", Material::stone_layer()),
+            ("stone_bricks", Material::stone_wall()),
+            ("sand", Material::sand_top()),
+            ("obsidian", Material::obsidian_block()),
+            ("oak_planks", Material::wood_planks()),
+            ("oak_log", Material::wood_block()),
+            ("birch_log", Material::birch_log()),
+            ("oak_leaves", Material::leaves_block()),
+            ("spruce_leaves", Material::spruce_leaves()),
+            ("cactus", Material::cactus_block()),
+            ("crystal", Material::crystal_block()),
+            ("glass", Material::clear_glass()),
+            ("iron_block", Material::metal_surface()),
+            ("coal_ore", Material::coal_ore()),
+            ("iron_ore", Material::iron_ore()),
+            ("gold_ore", Material::gold_ore()),
+            ("redstone_ore", Material::redstone_ore()),
+            ("diamond_ore", Material::diamond_ore()),
+            ("lava", Material::lava_surface()),
+        ];
+        for (name, material) in full {
+            registry.register(name, material, BlockShape::Full);
+        }
+        for (name, [r, g, b]) in FLOWERS {
+            registry.register(name, Material::flower(Color::new(r, g, b)), BlockShape::Small(0.35));
+        }
+        registry.register("water", Material::water_surface(), BlockShape::Liquid);
+        registry
+    }
+
+    // si el nombre ya existe devuelve el id que tenía
+    pub fn register(&mut self, name: &str, material: Material, shape: BlockShape) -> BlockId {
+        if let Some(&id) = self.by_name.get(name) {
+            return id;
+        }
+        let id = BlockId(self.blocks.len() as u16);
+        self.blocks.push(BlockDef { name: name.to_string(), material, shape });
+        self.by_name.insert(name.to_string(), id);
+        id
+    }
+
+    pub fn get(&self, id: BlockId) -> &BlockDef {
+        &self.blocks[id.0 as usize]
+    }
+
+    pub fn id(&self, name: &str) -> Option<BlockId> {
+        self.by_name.get(name).copied()
+    }
+
+    // bloque liso de un color, para modelos importados; el nombre lleva el color (y la opacidad)
+    // para que un mundo guardado lo pueda volver a armar
+    pub fn colored(&mut self, [r, g, b, a]: [u8; 4]) -> BlockId {
+        let name = format!("color_{:02x}{:02x}{:02x}{:02x}", r, g, b, a);
+        if let Some(id) = self.id(&name) {
+            return id;
+        }
+        let color = Color::new(r, g, b);
+        let material = if a < 255 { Material::tinted_glass(color) } else { Material::new(color) };
+        self.register(&name, material, BlockShape::Full)
+    }
+
+    // como `id`, pero los bloques de color que todavía no están se registran
+    pub fn resolve(&mut self, name: &str) -> Option<BlockId> {
+        if let Some(id) = self.id(name) {
+            return Some(id);
+        }
+        let hex = name.strip_prefix("color_").filter(|hex| hex.len() == 8)?;
+        let channel = |i: usize| u8::from_str_radix(hex.get(i * 2..i * 2 + 2)?, 16).ok();
+        Some(self.colored([channel(0)?, channel(1)?, channel(2)?, channel(3)?]))
+    }
+}
@@ -0,0 +1,117 @@
+use nalgebra_glm::{Vec3, dot, normalize, cross};
+use std::f32::consts::PI;
+use crate::material::{Material, SpecularModel};
+use crate::rng::Rng;
+
+// debajo de esto la reflexión se trata como espejo perfecto
+pub const MIRROR_ROUGHNESS: f32 = 0.05;
+
+// alpha = roughness², la parametrización habitual de Disney/UE4
+fn alpha(roughness: f32) -> f32 {
+    (roughness * roughness).max(1e-4)
+}
+
+// distribución de microfacetas GGX (Trowbridge-Reitz)
+pub fn ggx_distribution(n_dot_h: f32, roughness: f32) -> f32 {
+    let a2 = alpha(roughness) * alpha(roughness);
+    let d = n_dot_h * n_dot_h * (a2 - 1.0) + 1.0;
+    a2 / (PI * d * d)
+}
+
+// sombreado-enmascarado de Smith con la aproximación de Schlick-GGX
+pub fn smith_geometry(n_dot_v: f32, n_dot_l: f32, roughness: f32) -> f32 {
+    let k = alpha(roughness) / 2.0;
+    let g1 = |x: f32| x / (x * (1.0 - k) + k);
+    g1(n_dot_v) * g1(n_dot_l)
+}
+
+pub fn fresnel_schlick(cos_theta: f32, f0: Vec3) -> Vec3 {
+    let f = (1.0 - cos_theta.clamp(0.0, 1.0)).powi(5);
+    f0 + (Vec3::new(1.0, 1.0, 1.0) - f0) * f
+}
+
+// igual que Schlick pero las superficies rugosas no llegan a reflejo total en el borde
+pub fn fresnel_schlick_roughness(cos_theta: f32, f0: Vec3, roughness: f32) -> Vec3 {
+    let f = (1.0 - cos_theta.clamp(0.0, 1.0)).powi(5);
+    let edge = Vec3::new(
+        (1.0 - roughness).max(f0.x),
+        (1.0 - roughness).max(f0.y),
+        (1.0 - roughness).max(f0.z),
+    );
+    f0 + (edge - f0) * f
+}
+
+// reflectancia a incidencia normal: dieléctrico por IOR, metal toma el color base
+pub fn base_reflectance(material: &Material, base_color: &Vec3) -> Vec3 {
+    let ior = material.refractive_index.max(1.0);
+    let r0 = ((ior - 1.0) / (ior + 1.0)).powi(2);
+    let dielectric = Vec3::new(r0, r0, r0);
+    dielectric * (1.0 - material.metalness) + base_color * material.metalness
+}
+
+// término especular de Cook-Torrance, ya multiplicado por n·l
+pub fn evaluate_specular(normal: &Vec3, view: &Vec3, light: &Vec3, f0: Vec3, roughness: f32) -> Vec3 {
+    let n_dot_l = dot(normal, light);
+    let n_dot_v = dot(normal, view);
+    if n_dot_l <= 0.0 || n_dot_v <= 0.0 {
+        return Vec3::zeros();
+    }
+    let half = normalize(&(view + light));
+    let n_dot_h = dot(normal, &half).max(0.0);
+    let v_dot_h = dot(view, &half).max(0.0);
+
+    let d = ggx_distribution(n_dot_h, roughness);
+    let g = smith_geometry(n_dot_v, n_dot_l, roughness);
+    let f = fresnel_schlick(v_dot_h, f0);
+    f * (d * g / (4.0 * n_dot_v))
+}
+
+// pdf de muestrear `light` eligiendo la media-vector con la distribución GGX
+pub fn sample_pdf(normal: &Vec3, view: &Vec3, light: &Vec3, roughness: f32) -> f32 {
+    let half = normalize(&(view + light));
+    let n_dot_h = dot(normal, &half).max(0.0);
+    let v_dot_h = dot(view, &half).max(1e-4);
+    ggx_distribution(n_dot_h, roughness) * n_dot_h / (4.0 * v_dot_h)
+}
+
+// media-vector muestreado según D(h)·(n·h), alrededor de la normal
+pub fn sample_half_vector(normal: &Vec3, roughness: f32, rng: &mut Rng) -> Vec3 {
+    let a = alpha(roughness);
+    let u1 = rng.next_f32();
+    let u2 = rng.next_f32();
+
+    let phi = 2.0 * PI * u1;
+    let cos_theta = ((1.0 - u2) / (1.0 + (a * a - 1.0) * u2)).sqrt();
+    let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+
+    let helper = if normal.x.abs() > 0.9 { Vec3::new(0.0, 1.0, 0.0) } else { Vec3::new(1.0, 0.0, 0.0) };
+    let tangent = normalize(&cross(&helper, normal));
+    let bitangent = cross(normal, &tangent);
+
+    normalize(&(tangent * (sin_theta * phi.cos()) + bitangent * (sin_theta * phi.sin()) + normal * cos_theta))
+}
+
+// brillo especular de una luz puntual (sin color de la luz), en unidades de color 0..1
+pub fn specular_highlight(material: &Material, normal: &Vec3, view: &Vec3, light: &Vec3) -> Vec3 {
+    let tint = Vec3::new(
+        material.specular_color.r as f32 / 255.0,
+        material.specular_color.g as f32 / 255.0,
+        material.specular_color.b as f32 / 255.0,
+    ) * material.specular_strength;
+
+    match material.specular_model {
+        SpecularModel::BlinnPhong => {
+            let n_dot_l = dot(normal, light);
+            if n_dot_l <= 0.0 || dot(normal, view) <= 0.0 {
+                return Vec3::zeros();
+            }
+            let half = normalize(&(view + light));
+            let exponent = material.specular.max(1.0);
+            // normalizado para que subir el exponente no apague el brillo
+            let lobe = (exponent + 8.0) / (8.0 * PI) * dot(normal, &half).max(0.0).powf(exponent);
+            tint * (lobe * n_dot_l)
+        }
+        // el difuso del renderer no divide por PI, así que aquí se multiplica
+        SpecularModel::Ggx => evaluate_specular(normal, view, light, tint, material.roughness) * PI,
+    }
+}
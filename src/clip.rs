@@ -0,0 +1,51 @@
+use nalgebra_glm::Vec3;
+
+// caja de corte alineada a los bloques: solo se dibuja lo que tiene el centro adentro.
+// las caras que quedan sobre un plano que atraviesa el diorama son la "rebanada"
+#[derive(Debug, Clone, Copy)]
+pub struct ClipBox {
+    pub enabled: bool,
+    pub min: Vec3,
+    pub max: Vec3,
+    bounds_max: Vec3,  // hasta dónde llega el diorama; un plano ahí no corta nada
+    block_size: f32,
+}
+
+impl ClipBox {
+    // arranca apagada, con el corte a un tercio de los lados +x y +z, los que mira la cámara
+    pub fn around(bounds_min: Vec3, bounds_max: Vec3, block_size: f32) -> Self {
+        let mut clip = ClipBox {
+            enabled: false,
+            min: bounds_min,
+            max: bounds_max,
+            bounds_max,
+            block_size,
+        };
+        let blocks = ((bounds_max.x - bounds_min.x) / block_size / 3.0).round() as i32;
+        clip.move_plane(0, -blocks);
+        clip.move_plane(2, -blocks);
+        clip
+    }
+
+    // mueve el plano superior del eje (0 = x, 1 = y, 2 = z) de a bloques; negativo = corta más
+    pub fn move_plane(&mut self, axis: usize, blocks: i32) {
+        let moved = self.max[axis] + blocks as f32 * self.block_size;
+        // sin clamp: con una caja vacía (diorama sin bloques) el mínimo queda arriba del máximo
+        self.max[axis] = moved.max(self.min[axis] + self.block_size).min(self.bounds_max[axis]);
+    }
+
+    pub fn contains(&self, point: &Vec3) -> bool {
+        !self.enabled || (0..3).all(|i| point[i] >= self.min[i] && point[i] <= self.max[i])
+    }
+
+    // la cara está sobre un plano que se metió dentro del diorama (no sobre su borde original)
+    pub fn is_cut_face(&self, point: &Vec3, normal: &Vec3) -> bool {
+        if !self.enabled {
+            return false;
+        }
+        let eps = self.block_size * 0.01;
+        (0..3).any(|i| {
+            normal[i] > 0.5 && self.max[i] < self.bounds_max[i] - eps && (point[i] - self.max[i]).abs() < eps
+        })
+    }
+}
@@ -115,4 +115,22 @@ impl Cube {
             (u, v)
         }
     }
+    // tangente (dP/du) y bitangente (dP/dv) de la cara, siguiendo el mismo mapeo que get_uv_coordinates
+    pub fn get_tangent_frame(&self, point: &Vec3) -> (Vec3, Vec3) {
+        let center = (self.min + self.max) * 0.5;
+        let size = self.max - self.min;
+        let p = point - center;
+
+        let abs_x = (p.x / (size.x * 0.5)).abs();
+        let abs_y = (p.y / (size.y * 0.5)).abs();
+        let abs_z = (p.z / (size.z * 0.5)).abs();
+
+        if abs_x > abs_y && abs_x > abs_z {
+            (Vec3::new(0.0, 0.0, 1.0), Vec3::new(0.0, -1.0, 0.0))
+        } else if abs_y > abs_x && abs_y > abs_z {
+            (Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 1.0))
+        } else {
+            (Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, -1.0, 0.0))
+        }
+    }
 }
\ No newline at end of file
@@ -0,0 +1,359 @@
+use crate::OptimizedDiorama;
+use crate::material::{Material, MaterialType};
+use crate::texture::BlockTextures;
+use nalgebra_glm::{Vec3, cross, dot};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::Write as _;
+
+// cara para exportar (rectángulo o triángulo): esquinas en sentido antihorario vistas desde afuera,
+// uv con v hacia abajo de la imagen, como en `Texture::sample`
+struct Face {
+    corners: Vec<Vec3>,
+    uvs: Vec<[f32; 2]>,
+    normal: Vec3,
+    material: usize,
+}
+
+// el mismo mapeo que `Cube::get_uv_coordinates` y `Quad::get_uv_coordinates`, sin cortar en cada bloque
+fn face_uv(axis: usize, local: Vec3) -> [f32; 2] {
+    match axis {
+        0 => [local.z, 1.0 - local.y],
+        1 => [local.x, local.z],
+        _ => [local.x, 1.0 - local.y],
+    }
+}
+
+// rectángulo alineado a los ejes: `axis` es el eje de la normal y `positive` su sentido
+struct Rect {
+    axis: usize,
+    positive: bool,
+    min: Vec3,
+    max: Vec3,
+}
+
+// la geometría que dibuja el render: los quads del mallado greedy (ya fusionados), las caras
+// de los cubos que no se fusionan (menos las que quedan entre dos iguales), las superficies de los fluidos
+// y los triángulos de los modelos .obj puestos
+struct Scene {
+    faces: Vec<Face>,
+    materials: Vec<Material>,
+}
+
+impl Scene {
+    // sin nada a la vista no hay archivo que escribir (un glTF necesita al menos una primitiva)
+    fn collect(diorama: &OptimizedDiorama) -> Result<Self, Box<dyn Error>> {
+        let mut scene = Scene { faces: Vec::new(), materials: Vec::new() };
+        for quad in diorama.quads() {
+            let rect = Rect { axis: quad.axis, positive: quad.normal[quad.axis] > 0.0, min: quad.min, max: quad.max };
+            scene.add_rect(rect, quad.min, quad.tile_size, quad.material);
+        }
+        for cube in diorama.cubes() {
+            let size = cube.max.x - cube.min.x;
+            for axis in 0..3 {
+                for positive in [false, true] {
+                    let mut normal = Vec3::zeros();
+                    normal[axis] = if positive { 1.0 } else { -1.0 };
+                    if diorama.is_inner_face(cube, &normal) {
+                        continue;
+                    }
+                    let (mut min, mut max) = (cube.min, cube.max);
+                    if positive { min[axis] = cube.max[axis] } else { max[axis] = cube.min[axis] }
+                    scene.add_rect(Rect { axis, positive, min, max }, cube.min, size, cube.material);
+                }
+            }
+        }
+        for surface in diorama.water_surfaces.iter().chain(&diorama.lava_surfaces) {
+            let min = Vec3::new(surface.min_x, surface.height, surface.min_z);
+            let max = Vec3::new(surface.max_x, surface.height, surface.max_z);
+            scene.add_rect(Rect { axis: 1, positive: true, min, max }, min, surface.tile_size, surface.material);
+        }
+        for triangle in diorama.meshes.iter().flat_map(|mesh| &mesh.triangles) {
+            let material = scene.material_index(triangle.material);
+            let uvs = triangle.uvs.iter().map(|&(u, v)| [u, v]).collect();
+            scene.faces.push(Face { corners: triangle.vertices.to_vec(), uvs, normal: triangle.get_normal(), material });
+        }
+        // agrupadas por material, para escribir cada grupo de una vez
+        scene.faces.sort_by_key(|face| face.material);
+        if scene.faces.is_empty() {
+            return Err("no hay nada a la vista para exportar".into());
+        }
+        Ok(scene)
+    }
+
+    fn add_rect(&mut self, rect: Rect, uv_origin: Vec3, tile: f32, material: Material) {
+        let Rect { axis, positive, min, max } = rect;
+        let (a, b) = ((axis + 1) % 3, (axis + 2) % 3);
+        let mut corner_a = min;
+        corner_a[a] = max[a];
+        let mut corner_b = min;
+        corner_b[b] = max[b];
+        let mut corners = [min, corner_a, max, corner_b];
+        let mut normal = Vec3::zeros();
+        normal[axis] = if positive { 1.0 } else { -1.0 };
+        if dot(&cross(&(corners[1] - corners[0]), &(corners[3] - corners[0])), &normal) < 0.0 {
+            corners.swap(1, 3);
+        }
+        let uvs = corners.iter().map(|&corner| face_uv(axis, (corner - uv_origin) / tile)).collect();
+        let material = self.material_index(material);
+        self.faces.push(Face { corners: corners.to_vec(), uvs, normal, material });
+    }
+
+    fn material_index(&mut self, material: Material) -> usize {
+        match self.materials.iter().position(|m| *m == material) {
+            Some(index) => index,
+            None => {
+                self.materials.push(material);
+                self.materials.len() - 1
+            }
+        }
+    }
+
+    fn material_name(&self, index: usize) -> String {
+        format!("{:?}_{}", self.materials[index].material_type, index).to_lowercase()
+    }
+
+    // el render no usa la textura en vidrio y metal, solo el color
+    fn texture_type(material: &Material, textures: &BlockTextures) -> Option<MaterialType> {
+        let textured = material.has_texture && !matches!(material.material_type, MaterialType::Glass | MaterialType::Metal);
+        (textured && textures.albedo(material.material_type).is_some()).then_some(material.material_type)
+    }
+
+    // guarda como PNG cada textura que se usa, al lado del archivo exportado; devuelve los nombres
+    fn write_textures(&self, base: &str, textures: &BlockTextures) -> Result<HashMap<MaterialType, String>, Box<dyn Error>> {
+        let mut files = HashMap::new();
+        for material in &self.materials {
+            let Some(material_type) = Self::texture_type(material, textures) else { continue };
+            if files.contains_key(&material_type) {
+                continue;
+            }
+            let texture = textures.albedo(material_type).unwrap();
+            let file = format!("{}_{}.png", file_name(base), format!("{:?}", material_type).to_lowercase());
+            let image = image::RgbImage::from_raw(texture.width, texture.height, texture.data.clone()).ok_or("textura con tamaño inválido")?;
+            image.save(sibling(base, &file))?;
+            files.insert(material_type, file);
+        }
+        Ok(files)
+    }
+}
+
+fn file_name(base: &str) -> &str {
+    std::path::Path::new(base).file_name().and_then(|name| name.to_str()).unwrap_or(base)
+}
+
+// un archivo en la misma carpeta que `base`
+fn sibling(base: &str, file: &str) -> std::path::PathBuf {
+    std::path::Path::new(base).with_file_name(file)
+}
+
+fn unit(c: u8) -> f32 {
+    c as f32 / 255.0
+}
+
+// índices compartidos para valores repetidos (por los bits, así no hace falta Hash en f32)
+struct Dedup<const N: usize> {
+    values: Vec<[f32; N]>,
+    index: HashMap<[u32; N], usize>,
+}
+
+impl<const N: usize> Dedup<N> {
+    fn new() -> Self {
+        Dedup { values: Vec::new(), index: HashMap::new() }
+    }
+
+    fn insert(&mut self, value: [f32; N]) -> usize {
+        *self.index.entry(value.map(f32::to_bits)).or_insert_with(|| {
+            self.values.push(value);
+            self.values.len() - 1
+        })
+    }
+}
+
+// `base` sin extensión: escribe base.obj, base.mtl y las texturas
+pub fn write_obj(base: &str, diorama: &OptimizedDiorama, textures: &BlockTextures) -> Result<usize, Box<dyn Error>> {
+    let scene = Scene::collect(diorama)?;
+    let texture_files = scene.write_textures(base, textures)?;
+
+    let mut mtl = String::new();
+    for (index, material) in scene.materials.iter().enumerate() {
+        let d = material.diffuse;
+        writeln!(mtl, "newmtl {}", scene.material_name(index))?;
+        writeln!(mtl, "Kd {} {} {}", unit(d.r), unit(d.g), unit(d.b))?;
+        let s = material.specular_strength;
+        let sc = material.specular_color;
+        writeln!(mtl, "Ks {} {} {}", unit(sc.r) * s, unit(sc.g) * s, unit(sc.b) * s)?;
+        writeln!(mtl, "Ns {}", material.specular)?;
+        writeln!(mtl, "Ni {}", material.refractive_index)?;
+        if material.is_transparent() {
+            writeln!(mtl, "d {}", 1.0 - material.albedo[1])?;
+        }
+        if material.is_emissive() {
+            let e = material.emission_color();
+            let i = material.emission_intensity();
+            writeln!(mtl, "Ke {} {} {}", unit(e.r) * i, unit(e.g) * i, unit(e.b) * i)?;
+        }
+        writeln!(mtl, "illum 2")?;
+        if let Some(file) = Scene::texture_type(material, textures).and_then(|t| texture_files.get(&t)) {
+            writeln!(mtl, "map_Kd {}", file)?;
+        }
+        writeln!(mtl)?;
+    }
+
+    let (mut positions, mut uvs, mut normals) = (Dedup::<3>::new(), Dedup::<2>::new(), Dedup::<3>::new());
+    let mut faces = String::new();
+    let mut current = None;
+    for face in &scene.faces {
+        if current != Some(face.material) {
+            writeln!(faces, "usemtl {}", scene.material_name(face.material))?;
+            current = Some(face.material);
+        }
+        let n = normals.insert([face.normal.x, face.normal.y, face.normal.z]) + 1;
+        write!(faces, "f")?;
+        for (corner, uv) in face.corners.iter().zip(&face.uvs) {
+            let p = positions.insert([corner.x, corner.y, corner.z]) + 1;
+            // en OBJ la v crece hacia arriba de la imagen
+            let t = uvs.insert([uv[0], 1.0 - uv[1]]) + 1;
+            write!(faces, " {}/{}/{}", p, t, n)?;
+        }
+        writeln!(faces)?;
+    }
+
+    let mut obj = format!("# diorama exportado: {} caras\nmtllib {}.mtl\n", scene.faces.len(), file_name(base));
+    for [x, y, z] in &positions.values {
+        writeln!(obj, "v {} {} {}", x, y, z)?;
+    }
+    for [u, v] in &uvs.values {
+        writeln!(obj, "vt {} {}", u, v)?;
+    }
+    for [x, y, z] in &normals.values {
+        writeln!(obj, "vn {} {} {}", x, y, z)?;
+    }
+    obj.push_str(&faces);
+
+    std::fs::write(format!("{}.mtl", base), mtl)?;
+    std::fs::write(format!("{}.obj", base), obj)?;
+    Ok(scene.faces.len())
+}
+
+// `base` sin extensión: escribe base.gltf, base.bin y las texturas. una primitiva por material
+pub fn write_gltf(base: &str, diorama: &OptimizedDiorama, textures: &BlockTextures) -> Result<usize, Box<dyn Error>> {
+    let scene = Scene::collect(diorama)?;
+    let texture_files = scene.write_textures(base, textures)?;
+    let texture_index: HashMap<MaterialType, usize> = texture_files.keys().enumerate().map(|(i, &t)| (t, i)).collect();
+
+    let mut buffer: Vec<u8> = Vec::new();
+    let (mut views, mut accessors, mut primitives) = (Vec::new(), Vec::new(), Vec::new());
+    // agrega los datos al buffer con su bufferView y su accessor; devuelve el índice del accessor
+    let mut push = |data: &[u8], target: u32, accessor: String, buffer: &mut Vec<u8>| {
+        views.push(format!(r#"{{"buffer":0,"byteOffset":{},"byteLength":{},"target":{}}}"#, buffer.len(), data.len(), target));
+        buffer.extend_from_slice(data);
+        accessors.push(accessor.replace("VIEW", &(views.len() - 1).to_string()));
+        accessors.len() - 1
+    };
+
+    let mut start = 0;
+    while start < scene.faces.len() {
+        let material = scene.faces[start].material;
+        let end = scene.faces[start..].iter().position(|face| face.material != material).map_or(scene.faces.len(), |i| start + i);
+
+        let mut vertices = Dedup::<8>::new();
+        let mut indices: Vec<u32> = Vec::new();
+        for face in &scene.faces[start..end] {
+            let corners: Vec<u32> = face.corners.iter().zip(&face.uvs).map(|(p, t)| {
+                let n = face.normal;
+                vertices.insert([p.x, p.y, p.z, n.x, n.y, n.z, t[0], t[1]]) as u32
+            }).collect();
+            // en abanico desde la primera esquina
+            for i in 1..corners.len() - 1 {
+                indices.extend_from_slice(&[corners[0], corners[i], corners[i + 1]]);
+            }
+        }
+
+        let bytes = |range: std::ops::Range<usize>| -> Vec<u8> {
+            vertices.values.iter().flat_map(|v| v[range.clone()].iter().flat_map(|f| f.to_le_bytes())).collect()
+        };
+        let mut min = [f32::INFINITY; 3];
+        let mut max = [f32::NEG_INFINITY; 3];
+        for v in &vertices.values {
+            for i in 0..3 {
+                min[i] = min[i].min(v[i]);
+                max[i] = max[i].max(v[i]);
+            }
+        }
+        let count = vertices.values.len();
+        let position = push(&bytes(0..3), 34962, format!(
+            r#"{{"bufferView":VIEW,"componentType":5126,"count":{},"type":"VEC3","min":[{},{},{}],"max":[{},{},{}]}}"#,
+            count, min[0], min[1], min[2], max[0], max[1], max[2],
+        ), &mut buffer);
+        let normal = push(&bytes(3..6), 34962, format!(r#"{{"bufferView":VIEW,"componentType":5126,"count":{},"type":"VEC3"}}"#, count), &mut buffer);
+        let uv = push(&bytes(6..8), 34962, format!(r#"{{"bufferView":VIEW,"componentType":5126,"count":{},"type":"VEC2"}}"#, count), &mut buffer);
+        let index_bytes: Vec<u8> = indices.iter().flat_map(|i| i.to_le_bytes()).collect();
+        let index = push(&index_bytes, 34963, format!(r#"{{"bufferView":VIEW,"componentType":5125,"count":{},"type":"SCALAR"}}"#, indices.len()), &mut buffer);
+        primitives.push(format!(
+            r#"{{"attributes":{{"POSITION":{},"NORMAL":{},"TEXCOORD_0":{}}},"indices":{},"material":{}}}"#,
+            position, normal, uv, index, material,
+        ));
+        start = end;
+    }
+
+    // parámetros PBR; el vidrio y el agua dejan pasar la luz con transmisión e IOR
+    let mut materials = Vec::new();
+    let mut transmissive = false;
+    for (index, material) in scene.materials.iter().enumerate() {
+        let texture = Scene::texture_type(material, textures).map(|t| texture_index[&t]);
+        let d = material.diffuse;
+        let base_color = if texture.is_some() { [1.0; 3] } else { [unit(d.r), unit(d.g), unit(d.b)] };
+        let mut pbr = format!(
+            r#""baseColorFactor":[{},{},{},1],"metallicFactor":{},"roughnessFactor":{}"#,
+            base_color[0], base_color[1], base_color[2], material.metalness, material.roughness,
+        );
+        if let Some(texture) = texture {
+            write!(pbr, r#","baseColorTexture":{{"index":{}}}"#, texture)?;
+        }
+        let mut json = format!(r#"{{"name":"{}","pbrMetallicRoughness":{{{}}}"#, scene.material_name(index), pbr);
+        if material.is_emissive() {
+            let e = material.emission_color();
+            let i = material.emission_intensity();
+            write!(json, r#","emissiveFactor":[{},{},{}]"#, unit(e.r) * i, unit(e.g) * i, unit(e.b) * i)?;
+        }
+        if material.is_transparent() {
+            transmissive = true;
+            write!(json, r#","extensions":{{"KHR_materials_transmission":{{"transmissionFactor":{}}},"KHR_materials_ior":{{"ior":{}}}}}"#,
+                material.albedo[1], material.refractive_index)?;
+        }
+        json.push('}');
+        materials.push(json);
+    }
+
+    let mut files: Vec<_> = texture_files.iter().collect();
+    files.sort_by_key(|(t, _)| texture_index[*t]);
+    let images: Vec<String> = files.iter().map(|(_, file)| format!(r#"{{"uri":"{}"}}"#, file)).collect();
+    let texture_list: Vec<String> = (0..images.len()).map(|i| format!(r#"{{"sampler":0,"source":{}}}"#, i)).collect();
+    let extensions = if transmissive { r#""extensionsUsed":["KHR_materials_transmission","KHR_materials_ior"],"# } else { "" };
+    // glTF no acepta listas vacías: sin texturas visibles no van ni textures, ni images, ni samplers.
+    // texturas pixeladas (NEAREST) y repetidas (REPEAT), como en el render
+    let texture_arrays = if images.is_empty() {
+        String::new()
+    } else {
+        format!(
+            r#""textures":[{}],"images":[{}],"samplers":[{{"magFilter":9728,"minFilter":9728,"wrapS":10497,"wrapT":10497}}],
+"#,
+            texture_list.join(","), images.join(","),
+        )
+    };
+
+    let gltf = format!(
+        r#"{{"asset":{{"version":"2.0","generator":"diorama"}},{}"scene":0,"scenes":[{{"nodes":[0]}}],"nodes":[{{"mesh":0,"name":"diorama"}}],
+"meshes":[{{"primitives":[{}]}}],
+"materials":[{}],
+{}"accessors":[{}],
+"bufferViews":[{}],
+"buffers":[{{"uri":"{}.bin","byteLength":{}}}]}}
+"#,
+        extensions, primitives.join(","), materials.join(","), texture_arrays,
+        accessors.join(","), views.join(","), file_name(base), buffer.len(),
+    );
+    std::fs::write(format!("{}.bin", base), &buffer)?;
+    std::fs::write(format!("{}.gltf", base), gltf)?;
+    Ok(scene.faces.len())
+}
@@ -0,0 +1,141 @@
+use nalgebra_glm::{Vec3, normalize};
+use std::f32::consts::PI;
+use crate::material::Material;
+
+// una onda de Gerstner; la dirección es sobre el plano XZ
+#[derive(Debug, Clone, Copy)]
+pub struct Wave {
+    pub direction: (f32, f32),
+    pub amplitude: f32,
+    pub wavelength: f32,
+    pub speed: f32,
+    pub steepness: f32,  // 0 = senoidal, 1 = crestas puntiagudas
+}
+
+impl Wave {
+    pub fn new(direction: (f32, f32), amplitude: f32, wavelength: f32, speed: f32, steepness: f32) -> Self {
+        let len = (direction.0 * direction.0 + direction.1 * direction.1).sqrt().max(1e-6);
+        Wave { direction: (direction.0 / len, direction.1 / len), amplitude, wavelength, speed, steepness }
+    }
+
+    pub fn water_waves() -> Vec<Wave> {
+        vec![
+            Wave::new((1.0, 0.3), 0.012, 1.7, 0.6, 0.5),
+            Wave::new((-0.4, 1.0), 0.008, 0.9, 0.45, 0.6),
+            Wave::new((0.7, -0.8), 0.005, 0.55, 0.35, 0.4),
+        ]
+    }
+
+    // la lava es espesa: ondas largas, bajas y lentas
+    pub fn lava_waves() -> Vec<Wave> {
+        vec![
+            Wave::new((1.0, 0.6), 0.01, 2.4, 0.08, 0.3),
+            Wave::new((-0.5, 1.0), 0.006, 1.3, 0.05, 0.3),
+        ]
+    }
+
+    fn frequency(&self) -> f32 {
+        2.0 * PI / self.wavelength
+    }
+
+    fn phase(&self, x: f32, z: f32, time: f32) -> f32 {
+        let w = self.frequency();
+        w * (self.direction.0 * x + self.direction.1 * z) + self.speed * w * time
+    }
+}
+
+// superficie de un fluido sobre un rectángulo XZ, a una altura fija más las ondas
+pub struct FluidSurface {
+    pub min_x: f32,
+    pub max_x: f32,
+    pub min_z: f32,
+    pub max_z: f32,
+    pub height: f32,
+    pub bottom: f32,     // hasta dónde llega el volumen de fluido bajo la superficie
+    pub tile_size: f32,  // tamaño de un bloque, para repetir la textura
+    pub material: Material,
+    pub waves: Vec<Wave>,
+}
+
+impl FluidSurface {
+    pub fn new(min: (f32, f32), max: (f32, f32), height: f32, bottom: f32, tile_size: f32, material: Material, waves: Vec<Wave>) -> Self {
+        FluidSurface {
+            min_x: min.0,
+            max_x: max.0,
+            min_z: min.1,
+            max_z: max.1,
+            height,
+            bottom,
+            tile_size,
+            material,
+            waves,
+        }
+    }
+
+    // Gerstner mueve cada punto también de costado (Q·A·D·cos); para saber qué punto de la superficie
+    // quieta terminó en (x, z) se deshace ese corrimiento con unas iteraciones de punto fijo
+    fn undisplaced(&self, x: f32, z: f32, time: f32) -> (f32, f32) {
+        let (mut x0, mut z0) = (x, z);
+        for _ in 0..3 {
+            let (mut dx, mut dz) = (0.0, 0.0);
+            for w in &self.waves {
+                let c = w.steepness * w.amplitude * w.phase(x0, z0, time).cos();
+                dx += w.direction.0 * c;
+                dz += w.direction.1 * c;
+            }
+            (x0, z0) = (x - dx, z - dz);
+        }
+        (x0, z0)
+    }
+
+    // altura de la superficie de Gerstner sobre el punto (x, z) del plano
+    pub fn wave_height(&self, x: f32, z: f32, time: f32) -> f32 {
+        let (x0, z0) = self.undisplaced(x, z, time);
+        self.waves.iter().map(|w| w.amplitude * w.phase(x0, z0, time).sin()).sum()
+    }
+
+    pub fn ray_intersect(&self, ray_origin: &Vec3, ray_direction: &Vec3, time: f32) -> Option<f32> {
+        if ray_direction.y.abs() < 1e-6 {
+            return None;
+        }
+
+        // primero contra el plano quieto, luego se corrige un par de veces con la altura de las ondas
+        let mut t = (self.height - ray_origin.y) / ray_direction.y;
+        for _ in 0..2 {
+            let p = ray_origin + ray_direction * t;
+            let h = self.height + self.wave_height(p.x, p.z, time);
+            t = (h - ray_origin.y) / ray_direction.y;
+        }
+
+        if t <= 0.001 {
+            return None;
+        }
+        let p = ray_origin + ray_direction * t;
+        if p.x < self.min_x || p.x > self.max_x || p.z < self.min_z || p.z > self.max_z {
+            return None;
+        }
+        Some(t)
+    }
+
+    // normal de Gerstner (GPU Gems, cap. 1), en el punto de la superficie quieta que se movió hasta acá
+    pub fn get_normal(&self, point: &Vec3, time: f32) -> Vec3 {
+        let (x0, z0) = self.undisplaced(point.x, point.z, time);
+        let mut nx = 0.0;
+        let mut ny = 1.0;
+        let mut nz = 0.0;
+        for w in &self.waves {
+            let wa = w.frequency() * w.amplitude;
+            let phase = w.phase(x0, z0, time);
+            nx -= w.direction.0 * wa * phase.cos();
+            nz -= w.direction.1 * wa * phase.cos();
+            ny -= w.steepness * wa * phase.sin();
+        }
+        normalize(&Vec3::new(nx, ny, nz))
+    }
+
+    pub fn get_uv_coordinates(&self, point: &Vec3) -> (f32, f32) {
+        let u = ((point.x - self.min_x) / self.tile_size).fract();
+        let v = ((point.z - self.min_z) / self.tile_size).fract();
+        (u, v)
+    }
+}
@@ -0,0 +1,81 @@
+use crate::block::BlockId;
+use std::collections::VecDeque;
+
+// cuánta memoria pueden ocupar las ediciones guardadas (deshacer + rehacer)
+pub const HISTORY_LIMIT_BYTES: usize = 4 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy)]
+pub struct BlockChange {
+    pub position: [i32; 3],
+    pub before: BlockId,
+    pub after: BlockId,
+}
+
+// una operación entera (un bloque, un relleno...) se deshace de una vez
+#[derive(Debug, Clone)]
+pub struct Edit {
+    pub name: String,
+    pub changes: Vec<BlockChange>,
+}
+
+impl Edit {
+    // la edición que deja el mundo como estaba; se aplica en orden inverso
+    fn inverted(&self) -> Edit {
+        let changes = self.changes.iter().rev()
+            .map(|change| BlockChange { position: change.position, before: change.after, after: change.before })
+            .collect();
+        Edit { name: self.name.clone(), changes }
+    }
+
+    // lo que ocupa de verdad: la estructura, el buffer reservado de los cambios y el nombre
+    fn bytes(&self) -> usize {
+        std::mem::size_of::<Edit>() + self.changes.capacity() * std::mem::size_of::<BlockChange>() + self.name.capacity()
+    }
+}
+
+pub struct History {
+    undo: VecDeque<Edit>,
+    redo: Vec<Edit>,
+    limit_bytes: usize,
+}
+
+impl History {
+    pub fn new(limit_bytes: usize) -> Self {
+        History { undo: VecDeque::new(), redo: Vec::new(), limit_bytes }
+    }
+
+    // una edición nueva borra lo que se podía rehacer; si no entra, se olvidan las más viejas.
+    // la nueva se guarda siempre, aunque sola pase el límite, así un relleno grande se puede deshacer
+    pub fn push(&mut self, mut edit: Edit) {
+        if edit.changes.is_empty() {
+            return;
+        }
+        edit.changes.shrink_to_fit();
+        self.redo.clear();
+        self.undo.push_back(edit);
+        let mut bytes = self.bytes();
+        while bytes > self.limit_bytes && self.undo.len() > 1 {
+            if let Some(oldest) = self.undo.pop_front() {
+                bytes -= oldest.bytes();
+            }
+        }
+    }
+
+    // devuelve lo que hay que aplicar al mundo para deshacer la última edición
+    pub fn undo(&mut self) -> Option<Edit> {
+        let edit = self.undo.pop_back()?;
+        let inverse = edit.inverted();
+        self.redo.push(edit);
+        Some(inverse)
+    }
+
+    pub fn redo(&mut self) -> Option<Edit> {
+        let edit = self.redo.pop()?;
+        self.undo.push_back(edit.clone());
+        Some(edit)
+    }
+
+    fn bytes(&self) -> usize {
+        self.undo.iter().chain(&self.redo).map(Edit::bytes).sum()
+    }
+}
@@ -0,0 +1,164 @@
+use crate::rng::Rng;
+use crate::terrain::{Biome, Terrain};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+// agua cayendo por la columna (x, z): bloques desde `bottom` hasta `top`
+#[derive(Debug, Clone, Copy)]
+pub struct Waterfall {
+    pub x: usize,
+    pub z: usize,
+    pub bottom: usize,
+    pub top: usize,
+}
+
+// lo que el terreno tiene que aplicar: qué columnas subir, cuáles llevan agua y a qué nivel
+pub struct Hydrology {
+    pub filled: Vec<Vec<usize>>,         // altura tras rellenar los pozos demasiado chicos para ser lago
+    pub water: Vec<Vec<Option<usize>>>,  // nivel del agua de lagos y ríos
+    pub rivers: Vec<Vec<bool>>,          // cauces: se les quita el bloque de arriba
+    pub waterfalls: Vec<Waterfall>,
+}
+
+const MIN_RIVER_LENGTH: usize = 3;
+
+fn neighbors(x: usize, z: usize, size: usize) -> impl Iterator<Item = (usize, usize)> {
+    [(1, 0), (-1, 0), (0, 1), (0, -1)].into_iter().filter_map(move |(dx, dz)| {
+        let (nx, nz) = (x as i32 + dx, z as i32 + dz);
+        (nx >= 0 && nz >= 0 && nx < size as i32 && nz < size as i32).then_some((nx as usize, nz as usize))
+    })
+}
+
+pub fn analyze(terrain: &Terrain) -> Hydrology {
+    let size = terrain.size();
+    let config = &terrain.config;
+    let sea_level = config.sea_level;
+    let is_sea = |x: usize, z: usize| terrain.height(x, z) < sea_level;
+
+    // priority-flood: se inunda desde los bordes y el mar hacia adentro, siempre por el punto más bajo.
+    // `level` queda en la altura a la que llegaría el agua y `parent` apunta hacia donde desagua cada columna
+    let mut level = vec![vec![0; size]; size];
+    let mut parent: Vec<Vec<Option<(usize, usize)>>> = vec![vec![None; size]; size];
+    let mut visited = vec![vec![false; size]; size];
+    let mut queue = BinaryHeap::new();
+    for z in 0..size {
+        for x in 0..size {
+            if x == 0 || z == 0 || x == size - 1 || z == size - 1 || is_sea(x, z) {
+                visited[z][x] = true;
+                level[z][x] = terrain.height(x, z);
+                queue.push(Reverse((level[z][x], z, x)));
+            }
+        }
+    }
+    while let Some(Reverse((current, z, x))) = queue.pop() {
+        for (nx, nz) in neighbors(x, z, size) {
+            if visited[nz][nx] {
+                continue;
+            }
+            visited[nz][nx] = true;
+            level[nz][nx] = terrain.height(nx, nz).max(current);
+            parent[nz][nx] = Some((x, z));
+            queue.push(Reverse((level[nz][nx], nz, nx)));
+        }
+    }
+
+    // cada pozo es un lago si es grande y no toca el bioma volcánico; si no, se rellena de tierra
+    let mut filled: Vec<Vec<usize>> = (0..size).map(|z| (0..size).map(|x| terrain.height(x, z)).collect()).collect();
+    let mut water = vec![vec![None; size]; size];
+    let mut lake_cells = Vec::new();
+    let mut seen = vec![vec![false; size]; size];
+    for z in 0..size {
+        for x in 0..size {
+            if seen[z][x] || level[z][x] <= terrain.height(x, z) {
+                continue;
+            }
+            let mut basin = vec![(x, z)];
+            let mut stack = vec![(x, z)];
+            seen[z][x] = true;
+            while let Some((cx, cz)) = stack.pop() {
+                for (nx, nz) in neighbors(cx, cz, size) {
+                    if !seen[nz][nx] && level[nz][nx] > terrain.height(nx, nz) {
+                        seen[nz][nx] = true;
+                        basin.push((nx, nz));
+                        stack.push((nx, nz));
+                    }
+                }
+            }
+
+            let volcanic = basin.iter().any(|&(bx, bz)| terrain.biome(bx, bz) == Biome::Volcanic);
+            if basin.len() >= config.lake_min_area && !volcanic {
+                for &(bx, bz) in &basin {
+                    water[bz][bx] = Some(level[bz][bx]);
+                }
+                lake_cells.push(basin[0]);
+            } else {
+                for &(bx, bz) in &basin {
+                    filled[bz][bx] = level[bz][bx];
+                }
+            }
+        }
+    }
+
+    // nacientes: columnas altas y separadas entre sí, más la salida de cada lago
+    let mut candidates: Vec<(usize, usize)> = (0..size)
+        .flat_map(|z| (0..size).map(move |x| (x, z)))
+        .filter(|&(x, z)| water[z][x].is_none() && terrain.biome(x, z) != Biome::Volcanic
+            && filled[z][x] >= sea_level + 2)
+        .collect();
+    candidates.sort_by_key(|&(x, z)| Reverse(filled[z][x]));
+
+    let mut rng = Rng::new(config.seed ^ 0x0417_E125);
+    let spacing = (size / 3).max(2) as i32;
+    let mut springs: Vec<(usize, usize)> = Vec::new();
+    for (x, z) in candidates {
+        if springs.len() >= config.river_count {
+            break;
+        }
+        let far = springs.iter().all(|&(sx, sz)| (sx as i32 - x as i32).abs() + (sz as i32 - z as i32).abs() >= spacing);
+        if far && rng.next_f32() < 0.5 {
+            springs.push((x, z));
+        }
+    }
+    springs.extend(lake_cells);
+
+    // cada río baja siguiendo `parent` hasta el mar, el borde, la lava u otro río; los lagos los cruza.
+    // los que se cortan enseguida (un charco junto a la lava) no se marcan
+    let mut rivers = vec![vec![false; size]; size];
+    for spring in springs {
+        let mut course = Vec::new();
+        let mut cell = Some(spring);
+        while let Some((x, z)) = cell {
+            if is_sea(x, z) || terrain.biome(x, z) == Biome::Volcanic || rivers[z][x] {
+                break;
+            }
+            if water[z][x].is_none() {
+                course.push((x, z));
+            }
+            cell = parent[z][x];
+        }
+        if course.len() < MIN_RIVER_LENGTH {
+            continue;
+        }
+        for (x, z) in course {
+            rivers[z][x] = true;
+            water[z][x] = Some(filled[z][x]);
+        }
+    }
+
+    // donde el cauce baja de golpe, el agua cae por la columna de abajo
+    let mut waterfalls = Vec::new();
+    for z in 0..size {
+        for x in 0..size {
+            let (true, Some(top), Some((px, pz))) = (rivers[z][x], water[z][x], parent[z][x]) else { continue };
+            if terrain.biome(px, pz) == Biome::Volcanic {
+                continue;
+            }
+            let below = water[pz][px].or(is_sea(px, pz).then_some(sea_level));
+            if let Some(bottom) = below && top >= bottom + config.waterfall_drop {
+                waterfalls.push(Waterfall { x: px, z: pz, bottom: bottom + 1, top });
+            }
+        }
+    }
+
+    Hydrology { filled, water, rivers, waterfalls }
+}
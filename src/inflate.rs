@@ -0,0 +1,194 @@
+use std::error::Error;
+
+// descompresor de gzip/deflate (RFC 1951 y 1952), lo justo para los archivos de Minecraft
+
+const LENGTH_BASE: [u16; 29] = [3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131, 163, 195, 227, 258];
+const LENGTH_EXTRA: [u8; 29] = [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0];
+const DISTANCE_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537, 2049, 3073, 4097, 6145, 8193,
+    12289, 16385, 24577,
+];
+const DISTANCE_EXTRA: [u8; 30] = [0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13, 13];
+// orden en que vienen los largos del código de largos en un bloque dinámico
+const CODE_LENGTH_ORDER: [usize; 19] = [16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15];
+
+struct BitReader<'a> {
+    data: &'a [u8],
+    position: usize,
+    bit: u32,
+}
+
+impl<'a> BitReader<'a> {
+    fn bits(&mut self, count: u32) -> Result<u32, Box<dyn Error>> {
+        let mut value = 0;
+        for i in 0..count {
+            let byte = *self.data.get(self.position).ok_or("datos comprimidos incompletos")?;
+            value |= (((byte >> self.bit) & 1) as u32) << i;
+            self.bit += 1;
+            if self.bit == 8 {
+                self.bit = 0;
+                self.position += 1;
+            }
+        }
+        Ok(value)
+    }
+
+    fn align(&mut self) {
+        if self.bit > 0 {
+            self.bit = 0;
+            self.position += 1;
+        }
+    }
+}
+
+// código de Huffman canónico: cuántos símbolos hay de cada largo y los símbolos ordenados
+struct Huffman {
+    counts: [u16; 16],
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Self {
+        let mut counts = [0u16; 16];
+        for &len in lengths {
+            counts[len as usize] += 1;
+        }
+        counts[0] = 0;
+        let mut symbols: Vec<u16> = (0..lengths.len() as u16).filter(|&s| lengths[s as usize] > 0).collect();
+        symbols.sort_by_key(|&s| lengths[s as usize]);
+        Huffman { counts, symbols }
+    }
+
+    fn decode(&self, reader: &mut BitReader) -> Result<u16, Box<dyn Error>> {
+        let (mut code, mut first, mut index) = (0i32, 0i32, 0i32);
+        for len in 1..16 {
+            code |= reader.bits(1)? as i32;
+            let count = self.counts[len] as i32;
+            if code - first < count {
+                return Ok(self.symbols[(index + code - first) as usize]);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err("código de Huffman inválido".into())
+    }
+}
+
+fn fixed_codes() -> (Huffman, Huffman) {
+    let mut lengths = [0u8; 288];
+    for (symbol, len) in lengths.iter_mut().enumerate() {
+        *len = match symbol {
+            0..=143 => 8,
+            144..=255 => 9,
+            256..=279 => 7,
+            _ => 8,
+        };
+    }
+    (Huffman::new(&lengths), Huffman::new(&[5; 30]))
+}
+
+fn dynamic_codes(reader: &mut BitReader) -> Result<(Huffman, Huffman), Box<dyn Error>> {
+    let literals = reader.bits(5)? as usize + 257;
+    let distances = reader.bits(5)? as usize + 1;
+    let code_lengths = reader.bits(4)? as usize + 4;
+    let mut lengths = [0u8; 19];
+    for &symbol in &CODE_LENGTH_ORDER[..code_lengths] {
+        lengths[symbol] = reader.bits(3)? as u8;
+    }
+    let code_length_code = Huffman::new(&lengths);
+
+    let mut lengths = Vec::with_capacity(literals + distances);
+    while lengths.len() < literals + distances {
+        let symbol = code_length_code.decode(reader)?;
+        let (value, repeat) = match symbol {
+            0..=15 => (symbol as u8, 1),
+            16 => (*lengths.last().ok_or("repetición sin largo anterior")?, 3 + reader.bits(2)?),
+            17 => (0, 3 + reader.bits(3)?),
+            _ => (0, 11 + reader.bits(7)?),
+        };
+        lengths.extend(std::iter::repeat_n(value, repeat as usize));
+    }
+    if lengths.len() > literals + distances {
+        return Err("largos de código de más".into());
+    }
+    Ok((Huffman::new(&lengths[..literals]), Huffman::new(&lengths[literals..])))
+}
+
+pub fn inflate(data: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
+    let mut reader = BitReader { data, position: 0, bit: 0 };
+    let mut out: Vec<u8> = Vec::new();
+    loop {
+        let last = reader.bits(1)? == 1;
+        match reader.bits(2)? {
+            // sin comprimir: largo y su complemento, alineados al byte
+            0 => {
+                reader.align();
+                let header = data.get(reader.position..reader.position + 4).ok_or("bloque sin comprimir incompleto")?;
+                let len = u16::from_le_bytes([header[0], header[1]]) as usize;
+                reader.position += 4;
+                let bytes = data.get(reader.position..reader.position + len).ok_or("bloque sin comprimir incompleto")?;
+                out.extend_from_slice(bytes);
+                reader.position += len;
+            }
+            kind @ (1 | 2) => {
+                let (literal_code, distance_code) = if kind == 1 { fixed_codes() } else { dynamic_codes(&mut reader)? };
+                loop {
+                    let symbol = literal_code.decode(&mut reader)? as usize;
+                    if symbol < 256 {
+                        out.push(symbol as u8);
+                        continue;
+                    }
+                    if symbol == 256 {
+                        break;
+                    }
+                    let index = symbol - 257;
+                    let base = *LENGTH_BASE.get(index).ok_or("largo inválido")? as usize;
+                    let len = base + reader.bits(LENGTH_EXTRA[index] as u32)? as usize;
+                    let index = distance_code.decode(&mut reader)? as usize;
+                    let base = *DISTANCE_BASE.get(index).ok_or("distancia inválida")? as usize;
+                    let distance = base + reader.bits(DISTANCE_EXTRA[index] as u32)? as usize;
+                    if distance > out.len() {
+                        return Err("distancia más atrás que el principio".into());
+                    }
+                    // la copia puede pisar lo que va escribiendo (distancia menor que el largo)
+                    let start = out.len() - distance;
+                    for i in 0..len {
+                        out.push(out[start + i]);
+                    }
+                }
+            }
+            _ => return Err("tipo de bloque deflate inválido".into()),
+        }
+        if last {
+            return Ok(out);
+        }
+    }
+}
+
+// saca el encabezado de gzip (con los campos opcionales) y descomprime; si no es gzip lo deja igual
+pub fn gunzip(data: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
+    if !data.starts_with(&[0x1f, 0x8b]) {
+        return Ok(data.to_vec());
+    }
+    if data.get(2) != Some(&8) {
+        return Err("gzip con un método que no es deflate".into());
+    }
+    let flags = *data.get(3).ok_or("gzip incompleto")?;
+    let mut position = 10;
+    if flags & 4 != 0 {
+        let extra = data.get(position..position + 2).ok_or("gzip incompleto")?;
+        position += 2 + u16::from_le_bytes([extra[0], extra[1]]) as usize;
+    }
+    // nombre y comentario terminan en 0
+    for flag in [8, 16] {
+        if flags & flag != 0 {
+            let end = data.get(position..).and_then(|rest| rest.iter().position(|&b| b == 0)).ok_or("gzip incompleto")?;
+            position += end + 1;
+        }
+    }
+    if flags & 2 != 0 {
+        position += 2;
+    }
+    inflate(data.get(position..).ok_or("gzip incompleto")?)
+}
@@ -4,6 +4,34 @@ mod cube;
 mod camera;
 mod material;
 mod stats;
+mod texture;
+mod brdf;
+mod spectrum;
+mod medium;
+mod animated_texture;
+mod fluid;
+mod noise;
+mod rng;
+mod terrain;
+mod vegetation;
+mod ores;
+mod hydrology;
+mod clip;
+mod block;
+mod world;
+mod quad;
+mod mesh;
+mod picking;
+mod history;
+mod region;
+mod save;
+mod vox;
+mod inflate;
+mod nbt;
+mod schematic;
+mod export;
+mod triangle;
+mod obj;
 
 use framebuffer::Framebuffer;
 use color::Color;
@@ -11,14 +39,32 @@ use cube::Cube;
 use camera::OrbitCamera;
 use material::{Material, MaterialType};
 use stats::RenderStats;
+use texture::{BlockTextures, Texture};
+use rng::Rng;
+use clip::ClipBox;
+use block::{BlockId, BlockRegistry, BlockShape};
+use world::{RayHit, World};
+use quad::Quad;
+use triangle::{Triangle, TriangleMesh};
+use mesh::{ChunkMesh, MeshStats};
+use picking::{HOTBAR, Highlight};
+use region::{Clipboard, Region};
+use schematic::BlockMapping;
+use history::{BlockChange, Edit, History, HISTORY_LIMIT_BYTES};
+use std::collections::{BTreeMap, BTreeSet};
+use medium::MediumStack;
+use fluid::{FluidSurface, Wave};
+use terrain::{Biome, Terrain, TerrainConfig};
 use nalgebra_glm::{Vec3, normalize, dot};
-use minifb::{Key, Window, WindowOptions};
-use image::open;
+use minifb::{Key, KeyRepeat, MouseButton, MouseMode, Window, WindowOptions};
 use std::f32::consts::PI;
 
 const WIDTH: usize = 500;
 const HEIGHT: usize = 400;
 const MAX_DEPTH: u32 = 5;
+const GLOSSY_SAMPLES: u32 = 4;
+// como en Minecraft, el fluido no llena el bloque entero
+const FLUID_LEVEL: f32 = 0.875;
 
 pub struct Light {
     pub position: Vec3,
@@ -32,6 +78,15 @@ impl Light {
     }
 }
 
+// opciones del render que se pueden cambiar desde el teclado, y el tiempo de la animación
+#[derive(Default)]
+pub struct RenderSettings {
+    pub spectral_dispersion: bool,
+    pub time: f32,
+    pub highlight: Option<Highlight>,
+    pub selection: Option<(Vec3, Vec3)>,
+}
+
 pub struct Plane {
     pub point: Vec3,
     pub normal: Vec3,
@@ -59,218 +114,6 @@ impl Plane {
     }
 }
 
-#[derive(Clone)]
-pub struct Texture {
-    pub width: u32,
-    pub height: u32,
-    pub data: Vec<u8>,
-}
-
-impl Texture {
-    pub fn load_from_file(path: &str) -> Result<Self, Box<dyn std::error::Error>> {
-        let img = open(path)?;
-        let rgb_img = img.to_rgb8();
-        let (width, height) = rgb_img.dimensions();
-        let data = rgb_img.into_raw();
-        Ok(Texture { width, height, data })
-    }
-    
-    pub fn sample(&self, u: f32, v: f32) -> Color {
-        let u = (u.fract() + 1.0).fract().clamp(0.0, 1.0);
-        let v = (v.fract() + 1.0).fract().clamp(0.0, 1.0);
-        let x = ((u * self.width as f32) as u32).min(self.width - 1);
-        let y = ((v * self.height as f32) as u32).min(self.height - 1);
-        let index = ((y * self.width + x) * 3) as usize;
-        
-        if index + 2 < self.data.len() {
-            Color::new(self.data[index], self.data[index + 1], self.data[index + 2])
-        } else {
-            Color::new(255, 0, 255)
-        }
-    }
-    
-    pub fn create_grass_texture() -> Self {
-        let size = 32;
-        let mut data = Vec::with_capacity((size * size * 3) as usize);
-        for y in 0..size {
-            for x in 0..size {
-                let noise1 = ((x * 17 + y * 13) % 16) as f32 / 16.0;
-                let noise2 = ((x * 7 + y * 11) % 8) as f32 / 8.0;
-                let combined_noise = (noise1 + noise2 * 0.3).clamp(0.0, 1.0);
-                let base_green = 160 + (combined_noise * 60.0) as u8;
-                let r = (25.0 + combined_noise * 35.0) as u8;
-                let b = (25.0 + combined_noise * 30.0) as u8;
-                data.extend_from_slice(&[r, base_green, b]);
-            }
-        }
-        Texture { width: 32, height: 32, data }
-    }
-
-    pub fn create_cactus_texture() -> Self {
-        let size = 32;
-        let mut data = Vec::with_capacity((size * size * 3) as usize);
-        for y in 0..size {
-            for x in 0..size {
-                let stripe = if (x / 4) % 2 == 0 { 18 } else { 28 };
-                let g = 140 + stripe;
-                let r = 40 + (stripe / 2);
-                let b = 40 + (stripe / 3);
-                data.extend_from_slice(&[r as u8, g as u8, b as u8]);
-            }
-        }
-        Texture { width: size, height: size, data }
-    }
-    
-    pub fn create_stone_texture() -> Self {
-        let size = 32;
-        let mut data = Vec::with_capacity((size * size * 3) as usize);
-        for y in 0..size {
-            for x in 0..size {
-                let noise1 = ((x * 23 + y * 19) % 32) as f32 / 32.0;
-                let noise2 = ((x * 7 + y * 13) % 16) as f32 / 16.0;
-                let combined_noise = (noise1 + noise2 * 0.4).clamp(0.0, 1.0);
-                let base_gray = (70.0 + combined_noise * 40.0) as u8;
-                let variation = (combined_noise * 15.0) as u8;
-                data.extend_from_slice(&[ base_gray + variation, base_gray + (variation / 2), base_gray ]);
-            }
-        }
-        Texture { width: 32, height: 32, data }
-    }
-    
-    pub fn create_dirt_texture() -> Self {
-        let size = 32;
-        let mut data = Vec::with_capacity((size * size * 3) as usize);
-        for y in 0..size {
-            for x in 0..size {
-                let noise1 = ((x * 13 + y * 17) % 24) as f32 / 24.0;
-                let noise2 = ((x * 29 + y * 7) % 16) as f32 / 16.0;
-                let combined_noise = (noise1 + noise2 * 0.5).clamp(0.0, 1.0);
-                let brown_r = (140.0 + combined_noise * 50.0) as u8;
-                let brown_g = (85.0 + combined_noise * 35.0) as u8;
-                let brown_b = (35.0 + combined_noise * 25.0) as u8;
-                data.extend_from_slice(&[brown_r, brown_g, brown_b]);
-            }
-        }
-        Texture { width: 32, height: 32, data }
-    }
-    
-     
-     // agua más intensa
-     pub fn create_water_texture() -> Self {
-        let size = 32;
-        let mut data = Vec::with_capacity((size * size * 3) as usize);
-        for y in 0..size {
-            for x in 0..size {
-                
-                let w1 = ((x as f32 * 0.35).sin() + (y as f32 * 0.25).sin()) * 0.35;
-                let w2 = ((x as f32 * 0.18 + y as f32 * 0.22).sin()) * 0.25;
-                let w = (w1 + w2).clamp(-0.6, 0.6);
-
-            
-                let r = (15.0 + w * 10.0).round().clamp(0.0, 40.0) as u8;
-                let g = (90.0 + w * 25.0).round().clamp(70.0, 140.0) as u8;
-                let b = (205.0 + w * 40.0).round().clamp(160.0, 255.0) as u8;
-
-                data.extend_from_slice(&[r, g, b]);
-            }
-        }
-        Texture { width: size, height: size, data }
-    }
-
-
-    
-    pub fn create_lava_texture() -> Self {
-        let size = 32;
-        let mut data = Vec::with_capacity((size * size * 3) as usize);
-        for y in 0..size {
-            for x in 0..size {
-                let noise1 = ((x * 31 + y * 17) % 32) as f32 / 32.0;
-                let noise2 = ((x * 13 + y * 29) % 16) as f32 / 16.0;
-                let intensity = (noise1 + noise2 * 0.6).clamp(0.0, 1.0);
-                if intensity > 0.7 {
-                    data.extend_from_slice(&[255, 255, (150.0 + intensity * 105.0) as u8]);
-                } else if intensity > 0.4 {
-                    data.extend_from_slice(&[255, (120.0 + intensity * 135.0) as u8, 30]);
-                } else {
-                    data.extend_from_slice(&[(180.0 + intensity * 75.0) as u8, 20, 0]);
-                }
-            }
-        }
-        Texture { width: 32, height: 32, data }
-    }
-    
-    pub fn create_obsidian_texture() -> Self {
-        let size = 32;
-        let mut data = Vec::with_capacity((size * size * 3) as usize);
-        for y in 0..size {
-            for x in 0..size {
-                let noise1 = ((x * 43 + y * 23) % 16) as f32 / 16.0;
-                let noise2 = ((x * 17 + y * 31) % 8) as f32 / 8.0;
-                let combined_noise = (noise1 + noise2 * 0.2).clamp(0.0, 1.0);
-                
-                let base_intensity = 15.0 + combined_noise * 25.0;
-                let purple_tint = if combined_noise > 0.8 { 20.0 } else { 5.0 };
-                
-                let r = (base_intensity + purple_tint * 0.6) as u8;
-                let g = base_intensity as u8;
-                let b = (base_intensity + purple_tint) as u8;
-                
-                data.extend_from_slice(&[r, g, b]);
-            }
-        }
-        Texture { width: 32, height: 32, data }
-    }
-
-    // new for tree
-    pub fn create_sand_texture() -> Self {
-        let size = 32;
-        let mut data = Vec::with_capacity((size*size*3) as usize);
-        for y in 0..size {
-            for x in 0..size {
-                let n1 = ((x * 17 + y * 9) % 16) as f32 / 16.0;
-                let n2 = ((x * 5 + y * 13) % 8) as f32 / 8.0;
-                let t = (0.6 + 0.4*(n1*0.7 + n2*0.3)).clamp(0.0,1.0);
-                let r = (210.0 + 40.0*t) as u8;
-                let g = (190.0 + 35.0*t) as u8;
-                let b = (140.0 + 25.0*t) as u8;
-                data.extend_from_slice(&[r,g,b]);
-            }
-        }
-        Texture { width: size, height: size, data }
-    }
-
-    pub fn create_wood_texture() -> Self {
-        let size = 32;
-        let mut data = Vec::with_capacity((size*size*3) as usize);
-        for y in 0..size {
-            for x in 0..size {
-                let ring = (((x as f32 - 16.0).hypot(y as f32 - 16.0) * 0.4).sin()*0.5+0.5).clamp(0.0,1.0);
-                let r = (110.0 + 60.0*ring) as u8;
-                let g = (75.0 + 40.0*ring) as u8;
-                let b = (45.0 + 25.0*ring) as u8;
-                data.extend_from_slice(&[r,g,b]);
-            }
-        }
-        Texture { width: size, height: size, data }
-    }
-
-    pub fn create_leaves_texture() -> Self {
-        let size = 32;
-        let mut data = Vec::with_capacity((size*size*3) as usize);
-        for y in 0..size {
-            for x in 0..size {
-                let n = ((x*23 + y*31) % 32) as f32 / 32.0;
-                let r = (30.0 + 40.0*n) as u8;
-                let g = (120.0 + 100.0*n) as u8;
-                let b = (30.0 + 35.0*n) as u8;
-                data.extend_from_slice(&[r,g,b]);
-            }
-        }
-        Texture { width: size, height: size, data }
-    }
-
-}
-
 #[derive(Clone)]
 pub struct Skybox {
     pub px: Texture,
@@ -438,399 +281,382 @@ impl Skybox {
 }
 
 pub struct OptimizedDiorama {
-    pub cubes: Vec<Cube>,
-    pub water_planes: Vec<Plane>,
-    pub lava_planes: Vec<Plane>,
+    pub world: World,
+    // modelos importados (personajes, objetos); no son bloques, van aparte de las ediciones
+    pub meshes: Vec<TriangleMesh>,
+    // lo que recorre el render, un grupo por chunk; los impactos apuntan a (grupo, índice)
+    chunk_meshes: Vec<ChunkMesh>,
+    chunk_slots: BTreeMap<(i32, i32, i32), usize>,
+    history: History,
+    pub mesh_stats: MeshStats,
+    pub water_surfaces: Vec<FluidSurface>,
+    pub lava_surfaces: Vec<FluidSurface>,
     pub bounding_box_min: Vec3,
     pub bounding_box_max: Vec3,
+    pub clip: ClipBox,
+    center: Vec3,
+    cube_size: f32,
+    offset: f32,
 }
 
 impl OptimizedDiorama {
 
-    
-   
-    
-    pub fn new(center: Vec3, cube_size: f32) -> Self {
-        let mut cubes = Vec::new();
-        let mut water_planes = Vec::new();
-        let mut lava_planes = Vec::new();
-        
-        let grid_size = 18;
+    pub fn new(center: Vec3, cube_size: f32, config: &TerrainConfig) -> Self {
+        let mut water_surfaces = Vec::new();
+        let mut lava_surfaces = Vec::new();
+
+        let terrain = Terrain::generate(config);
+        let grid_size = terrain.size();
         let spacing = cube_size;
         let offset = (grid_size as f32 * spacing) / 2.0 - spacing / 2.0;
-        
-        let terrain_heights = Self::generate_terrain_heights(grid_size);
-        
-        let mut min_pos = Vec3::new(f32::INFINITY, f32::INFINITY, f32::INFINITY);
-        let mut max_pos = Vec3::new(f32::NEG_INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY);
-        
+
+        let mut world = World::new(BlockRegistry::with_defaults());
         for z in 0..grid_size {
             for x in 0..grid_size {
-                let height = terrain_heights[z][x];
-                
-                for y_level in 0..=height {
-                    let pos = Vec3::new(
-                        center.x + x as f32 * spacing - offset,
-                        center.y + (y_level as f32) * spacing,
-                        center.z + z as f32 * spacing - offset,
-                    );
-                    
-                    min_pos = Vec3::new(min_pos.x.min(pos.x), min_pos.y.min(pos.y), min_pos.z.min(pos.z));
-                    max_pos = Vec3::new(max_pos.x.max(pos.x), max_pos.y.max(pos.y), max_pos.z.max(pos.z));
-                    
-                    let material = Self::determine_material(x, z, y_level, height);
-                    
-                    if Self::should_place_cube(x, z, y_level, height, grid_size) {
-                        cubes.push(Cube::new(pos, cube_size, material));
+                for y_level in 0..=terrain.height(x, z) {
+                    if terrain.is_solid(x, y_level, z) {
+                        world.set_named(x as i32, y_level as i32, z as i32, terrain.block_name(x, z, y_level));
                     }
                 }
             }
         }
-        
-        Self::add_water_areas(&mut water_planes, &terrain_heights, center, cube_size, spacing, offset);
-        Self::add_lava_areas(&mut lava_planes, &terrain_heights, center, cube_size, spacing, offset);
-
-        Self::place_tree(&mut cubes, center, cube_size, spacing, offset);
-        //Self::place_forest_corner_details(&mut cubes, center, cube_size, spacing, offset);
-        Self::place_crystal_details(&mut cubes, center, cube_size, spacing, offset);
-        Self::place_overhang_roof(&mut cubes, center, cube_size, spacing, offset);
-
-        // cactus
-        Self::place_cactus(&mut cubes, center, cube_size, spacing, offset);
 
-      
+        Self::add_fluid_areas(&mut water_surfaces, &mut lava_surfaces, &terrain, (center, cube_size, offset));
+        Self::place_waterfalls(&mut world, &terrain);
 
-         
+        Self::place_vegetation(&mut world, &terrain);
+        Self::place_crystal_details(&mut world, &terrain);
 
+        Self::from_parts(world, water_surfaces, lava_surfaces, center, cube_size, offset)
+    }
 
-        
-        OptimizedDiorama { 
-            cubes, 
-            water_planes, 
-            lava_planes,
-            bounding_box_min: min_pos - Vec3::new(2.0, 2.0, 2.0),
-            bounding_box_max: max_pos + Vec3::new(2.0, 2.0, 2.0),
-        }
-
+    // arma el diorama con un mundo ya hecho (generado o leído de un archivo)
+    pub fn from_parts(world: World, water_surfaces: Vec<FluidSurface>, lava_surfaces: Vec<FluidSurface>,
+                      center: Vec3, cube_size: f32, offset: f32) -> Self {
+        let mut diorama = OptimizedDiorama {
+            world,
+            meshes: Vec::new(),
+            chunk_meshes: Vec::new(),
+            chunk_slots: BTreeMap::new(),
+            history: History::new(HISTORY_LIMIT_BYTES),
+            mesh_stats: MeshStats::default(),
+            water_surfaces,
+            lava_surfaces,
+            bounding_box_min: Vec3::zeros(),
+            bounding_box_max: Vec3::zeros(),
+            clip: ClipBox::around(Vec3::zeros(), Vec3::zeros(), cube_size),
+            center,
+            cube_size,
+            offset,
+        };
+        diorama.rebuild_mesh();
+        // la caja de corte se arma con el diorama entero, antes de cortar nada
+        diorama.clip = ClipBox::around(
+            diorama.bounding_box_min + Vec3::new(1.0, 1.0, 1.0),
+            diorama.bounding_box_max - Vec3::new(1.0, 1.0, 1.0),
+            cube_size,
+        );
+        diorama
+    }
 
+    // dónde queda el bloque (0, 0, 0) y de qué tamaño son los bloques: (centro, tamaño, desplazamiento)
+    pub fn layout(&self) -> (Vec3, f32, f32) {
+        (self.center, self.cube_size, self.offset)
     }
 
-    // fn fores
+    fn block_position(&self, x: i32, y_level: i32, z: i32) -> Vec3 {
+        Vec3::new(
+            self.center.x + x as f32 * self.cube_size - self.offset,
+            self.center.y + y_level as f32 * self.cube_size,
+            self.center.z + z as f32 * self.cube_size - self.offset,
+        )
+    }
 
-    fn place_forest_corner_details(
-        cubes: &mut Vec<Cube>, center: Vec3, cube_size: f32, spacing: f32, offset: f32
-    ) {
-        
-        for &(bx, bz) in &[(13,13), (14,12), (16,13)] {
-            for by in 0..2 {
-                let pos = Vec3::new(
-                    center.x + (bx as f32) * spacing - offset,
-                    center.y + (5 + by) as f32 * spacing,    
-                    center.z + (bz as f32) * spacing - offset,
-                );
-                cubes.push(Cube::new(pos, cube_size, Material::stone_layer()));
-            }
+    // geometría para el render, solo con los bloques con alguna cara a la vista y dentro del corte:
+    // los opacos se funden en quads (mallado greedy) y el resto (fluido, vidrio, flores) queda en cubos.
+    // hay que llamarla después de cambiar la caja de corte; al editar bloques alcanza con `edit`
+    pub fn rebuild_mesh(&mut self) {
+        let keys = self.world.chunk_keys();
+        self.chunk_meshes = keys.iter().map(|&key| self.mesh_chunk(key)).collect();
+        self.chunk_slots = keys.into_iter().enumerate().map(|(slot, key)| (key, slot)).collect();
+
+        self.mesh_stats = MeshStats { blocks: self.world.blocks().count(), ..MeshStats::default() };
+        for mesh in &self.chunk_meshes {
+            self.mesh_stats.add(mesh);
         }
+        self.update_bounds();
+    }
 
-        
-        for x in 15..=16 {
-            for z in 16..=17 {
-                for y in (4..=6).rev() { // para abajo
-                    let pos = Vec3::new(
-                        center.x + (x as f32) * spacing - offset,
-                        center.y + (y as f32) * spacing,
-                        center.z + (z as f32) * spacing - offset,
-                    );
-                    cubes.push(Cube::new(pos, cube_size, Material::leaves_block()));
-                }
-            }
-        }
+    pub fn cubes(&self) -> impl Iterator<Item = &Cube> {
+        self.chunk_meshes.iter().flat_map(|mesh| &mesh.cubes)
     }
 
-   
-
-        fn place_tree(cubes: &mut Vec<Cube>, center: Vec3, cube_size: f32,
-                        spacing: f32, offset: f32) {
-                // los cálculos del arbol
-               let tx: i32 = 14;
-                let tz: i32 = 13;
-                let base_y: i32 = 5;  
-                let trunk_h: i32 = 4;
-
-                // tronco
-                for i in 0..trunk_h {
-                    let pos = Vec3::new(
-                        center.x + (tx as f32) * spacing - offset,
-                        center.y + ((base_y + i) as f32) * spacing,
-                        center.z + (tz as f32) * spacing - offset,
-                    );
-                    
-                    cubes.push(Cube::new(pos, cube_size, Material::wood_block()));
-                    
-                }
+    pub fn quads(&self) -> impl Iterator<Item = &Quad> {
+        self.chunk_meshes.iter().flat_map(|mesh| &mesh.quads)
+    }
 
-                // copa 3x3x3 con redondeo de Manhattan
-                let top_y = base_y + trunk_h;
-                for dz in -1i32..=1 {
-                    for dx in -1i32..=1 {
-                        for dy in 0i32..=2 {
-                            let manhattan: i32 = dx.abs() + dy + dz.abs();
-                            if manhattan <= 3 {
-                                let pos = Vec3::new(
-                                    center.x + ((tx + dx) as f32) * spacing - offset,
-                                    center.y + ((top_y + dy) as f32) * spacing,
-                                    center.z + ((tz + dz) as f32) * spacing - offset,
-                                );
-                                cubes.push(Cube::new(pos, cube_size, Material::leaves_block()));
-                        }
-                    }
+    fn mesh_chunk(&self, key: (i32, i32, i32)) -> ChunkMesh {
+        let keep = |x, y, z| self.clip.contains(&self.block_position(x, y, z));
+        let (opaque, others): (Vec<_>, Vec<_>) = self.world.visible_blocks(key, keep)
+            .into_iter()
+            .partition(|&(_, _, _, id)| self.world.registry.get(id).is_opaque());
+
+        let cubes: Vec<Cube> = others.iter().map(|&(x, y, z, id)| {
+            let block = self.world.registry.get(id);
+            let mut pos = self.block_position(x, y, z);
+            let size = match block.shape {
+                // los bloques chicos se apoyan en el piso
+                BlockShape::Small(scale) => {
+                    pos.y -= (1.0 - scale) * self.cube_size / 2.0;
+                    self.cube_size * scale
                 }
-            }
-        }
+                // el fluido que cae empalma con la superficie de arriba y la de abajo
+                BlockShape::Liquid => {
+                    pos.y -= (1.0 - FLUID_LEVEL) * self.cube_size;
+                    self.cube_size
+                }
+                BlockShape::Full | BlockShape::Empty => self.cube_size,
+            };
+            Cube::new(pos, size, block.material)
+        }).collect();
+
+        let (faces, block_faces) = mesh::greedy_faces(&self.world, &opaque, keep);
+        let half = Vec3::new(1.0, 1.0, 1.0) * self.cube_size / 2.0;
+        let quads: Vec<Quad> = faces.iter().map(|face| {
+            let min = self.block_position(face.min[0], face.min[1], face.min[2]) - half;
+            let max = self.block_position(face.max[0], face.max[1], face.max[2]) - half;
+            Quad::new(face.axis, face.positive, min, max, self.cube_size, self.world.registry.get(face.id).material)
+        }).collect();
+
+        ChunkMesh::new(cubes, quads, opaque.len() + others.len(), block_faces)
+    }
 
-        fn in_oasis(x: usize, z: usize) -> bool {
-            // oasis de agua
-            let grid_back = 17;
-            (x >= 8 && x <= 10) && (z >= grid_back - 3 && z <= grid_back - 1)
+    // la caja envolvente incluye los árboles y los modelos; se arma con la caja de cada chunk
+    fn update_bounds(&mut self) {
+        let mut min_pos = Vec3::new(f32::INFINITY, f32::INFINITY, f32::INFINITY);
+        let mut max_pos = Vec3::new(f32::NEG_INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY);
+        let boxes = self.chunk_meshes.iter().filter_map(|m| m.bounds)
+            .chain(self.meshes.iter().filter_map(|m| m.bounds()));
+        for (min, max) in boxes {
+            min_pos = nalgebra_glm::min2(&min_pos, &min);
+            max_pos = nalgebra_glm::max2(&max_pos, &max);
         }
+        self.bounding_box_min = min_pos - Vec3::new(1.0, 1.0, 1.0);
+        self.bounding_box_max = max_pos + Vec3::new(1.0, 1.0, 1.0);
+    }
 
-        //cactus
-        fn place_cactus(
-            cubes: &mut Vec<Cube>, center: Vec3, cube_size: f32, spacing: f32, offset: f32) {
-            
-            let cx = 9usize;    
-            let cz = 8usize;    
+    // pone el modelo parado sobre el bloque, centrado en x y z, con `height` bloques de alto
+    pub fn place_mesh(&mut self, model: &TriangleMesh, [x, y, z]: [i32; 3], height: f32) {
+        let Some((min, max)) = model.bounds() else { return };
+        let scale = height * self.cube_size / (max.y - min.y).max(1e-6);
+        let base = self.block_position(x, y, z) - Vec3::new(0.0, self.cube_size / 2.0, 0.0);
+        let model_base = Vec3::new((min.x + max.x) / 2.0, min.y, (min.z + max.z) / 2.0) * scale;
+        self.meshes.push(model.transformed(scale, base - model_base));
+        self.update_bounds();
+    }
 
-           
-            let base_y = 4usize;
-            for y in base_y..=base_y + 1 {
-                let pos = Vec3::new(
-                    center.x + (cx as f32) * spacing - offset,
-                    center.y + (y as f32) * spacing,
-                    center.z + (cz as f32) * spacing - offset,
-                );
-                cubes.push(Cube::new(pos, cube_size, Material::cactus_block()));
+    pub fn remove_last_mesh(&mut self) -> bool {
+        let removed = self.meshes.pop().is_some();
+        self.update_bounds();
+        removed
+    }
+
+    // cambia bloques como una sola edición (se deshace de una vez); devuelve cuántos cambiaron
+    pub fn edit(&mut self, name: &str, blocks: impl IntoIterator<Item = ([i32; 3], BlockId)>) -> usize {
+        let mut changes = Vec::new();
+        for ([x, y, z], id) in blocks {
+            let before = self.world.get(x, y, z);
+            if before != id {
+                self.world.set(x, y, z, id);
+                changes.push(BlockChange { position: [x, y, z], before, after: id });
             }
         }
+        self.remesh_around(&changes);
+        let count = changes.len();
+        self.history.push(Edit { name: name.to_string(), changes });
+        count
+    }
 
+    pub fn undo(&mut self) -> Option<Edit> {
+        let edit = self.history.undo()?;
+        self.apply(&edit);
+        Some(edit)
+    }
 
-        // cave
-        fn place_crystal_details(
-            cubes: &mut Vec<Cube>, center: Vec3, cube_size: f32, spacing: f32, offset: f32) {
-            
+    pub fn redo(&mut self) -> Option<Edit> {
+        let edit = self.history.redo()?;
+        self.apply(&edit);
+        Some(edit)
+    }
 
-           
-            for &(cx, cz) in &[(2,8), (3,11)] {
-                for y in 3..=4 {
-                    let pos = Vec3::new(
-                        center.x + (cx as f32) * spacing - offset,
-                        center.y + (y as f32) * spacing,
-                        center.z + (cz as f32) * spacing - offset,
-                    );
-                    cubes.push(Cube::new(pos, cube_size, Material::crystal_block()));
-                }
-            }
+    // aplica una edición del historial sin volver a guardarla
+    fn apply(&mut self, edit: &Edit) {
+        for change in &edit.changes {
+            let [x, y, z] = change.position;
+            self.world.set(x, y, z, change.after);
         }
+        self.remesh_around(&edit.changes);
+    }
 
-        const ROOF_X0: usize = 0;   
-        const ROOF_Z0: usize = 0;  
-        const ROOF_W:  usize = 6;   
-        const ROOF_H:  usize = 7;   
-        const ROOF_Y:  usize = 7;  
-
-        fn in_roof_plate(x: usize, z: usize) -> bool {
-            (Self::ROOF_X0..Self::ROOF_X0 + Self::ROOF_W).contains(&x) &&
-            (Self::ROOF_Z0..Self::ROOF_Z0 + Self::ROOF_H).contains(&z)
+    // rehace solo los chunks de los bloques cambiados y de los vecinos que comparten caras con ellos;
+    // los contadores se ajustan con la diferencia de cada chunk rehecho
+    fn remesh_around(&mut self, changes: &[BlockChange]) {
+        if changes.is_empty() {
+            return;
         }
-
-        
-
-
-    
-    
-    const CORNER_X: usize = 17;  
-    const CORNER_Z: usize = 12; 
-    // terreno heights
-    fn generate_terrain_heights(grid_size: usize) -> Vec<Vec<usize>> {
-        
-        let mut heights = vec![vec![1; grid_size]; grid_size];
-
-        for z in 0..grid_size {
-            for x in 0..grid_size {
-                let h = if x < 6 {
-                    // cave  
-                    let mut h = if x == 0 || z == 0 { 6 } 
-                    else if x == 1 || z == 1 { 5 }        
-                    else if x == 2 || z == 2 { 4 }        
-                    else { 3 };                            
-
-                   
-
-                    h
-
-                } else if x < 12 {
-                    // oasis 
-                    if Self::in_oasis(x, z) { 2 } else { 3 }
-                } else {
-                    // forest 
-
-                        if x == 12 || x == 13 {
-                            3
-                        } else {
-                            //para orilla 
-                            let mut base = 4 + ((x + 2 * z) % 2) as usize; // 4–5
-
-                        
-                            let dx = (x as isize - Self::CORNER_X as isize).abs() as f32;
-                            let dz = (z as isize - Self::CORNER_Z as isize).abs() as f32;
-                            let dist = (dx * dx + dz * dz).sqrt();
-
-                            let bump = if dist < 2.5 { 2 } else if dist < 5.5 { 1 } else { 0 };
-                            base + bump
-                        }
-                   
-                };
-                heights[z][x] = h;
+        for change in changes {
+            self.mesh_stats.blocks = self.mesh_stats.blocks + !change.after.is_air() as usize - !change.before.is_air() as usize;
+        }
+        let neighbors = [(0, 0, 0), (1, 0, 0), (-1, 0, 0), (0, 1, 0), (0, -1, 0), (0, 0, 1), (0, 0, -1)];
+        let keys: BTreeSet<_> = changes.iter()
+            .flat_map(|change| {
+                let [x, y, z] = change.position;
+                neighbors.iter().map(move |&(dx, dy, dz)| World::chunk_key(x + dx, y + dy, z + dz))
+            })
+            .collect();
+        for key in keys {
+            let mesh = self.mesh_chunk(key);
+            self.mesh_stats.add(&mesh);
+            match self.chunk_slots.get(&key) {
+                Some(&slot) => {
+                    let old = std::mem::replace(&mut self.chunk_meshes[slot], mesh);
+                    self.mesh_stats.remove(&old);
+                }
+                None => {
+                    self.chunk_slots.insert(key, self.chunk_meshes.len());
+                    self.chunk_meshes.push(mesh);
+                }
             }
-        } 
-
-        heights
+        }
+        self.update_bounds();
     }
 
-    
-    // para la cave bioma 
-    fn in_lava_pond_a(x: usize, z: usize) -> bool {  
-        x >= 2 && x <= 4 && z >= 2 && z <= 4
+    // de coordenadas del mundo a coordenadas de bloque, donde el bloque (x, y, z) ocupa [x, x + 1)
+    fn to_block_space(&self, point: &Vec3) -> Vec3 {
+        let half = Vec3::new(1.0, 1.0, 1.0) * self.cube_size / 2.0;
+        (point - (self.block_position(0, 0, 0) - half)) / self.cube_size
     }
-    fn in_lava_pond_b(x: usize, z: usize) -> bool {  
-        x >= 1 && x <= 3 && z >= 4 && z <= 6.min(5)  
+
+    // el bloque que mira el rayo, sin contar lo que saca el corte
+    pub fn pick(&self, ray_origin: &Vec3, ray_direction: &Vec3) -> Option<RayHit> {
+        let keep = |x, y, z| self.clip.contains(&self.block_position(x, y, z));
+        let max_distance = nalgebra_glm::length(&(self.bounding_box_max - self.bounding_box_min)) / self.cube_size
+            + nalgebra_glm::length(&(ray_origin - self.center)) / self.cube_size;
+        self.world.raycast(self.to_block_space(ray_origin), *ray_direction, max_distance, keep)
     }
-    fn in_lava_pond_c(x: usize, z: usize, grid_size: usize) -> bool {
-        //del lado opuesto
-        x >= 2 && x <= 4 && z >= grid_size - 4 && z <= grid_size - 2
+
+    // la caja que ocupa una región en coordenadas del mundo
+    pub fn region_box(&self, region: &Region) -> (Vec3, Vec3) {
+        let half = Vec3::new(1.0, 1.0, 1.0) * self.cube_size / 2.0;
+        let [x0, y0, z0] = region.min;
+        let [x1, y1, z1] = region.max;
+        (self.block_position(x0, y0, z0) - half, self.block_position(x1, y1, z1) + half)
     }
 
-    fn in_any_lava_pond(x: usize, z: usize, grid_size: usize) -> bool {
-        Self::in_lava_pond_a(x, z)
-            || Self::in_lava_pond_b(x, z)
-            || Self::in_lava_pond_c(x, z, grid_size)
+    // la cara tocada en coordenadas del mundo, para resaltarla en el render
+    pub fn highlight(&self, hit: &RayHit) -> Highlight {
+        let [x, y, z] = hit.block;
+        let half = Vec3::new(1.0, 1.0, 1.0) * self.cube_size / 2.0;
+        let center = self.block_position(x, y, z);
+        let normal = Vec3::new(hit.normal[0] as f32, hit.normal[1] as f32, hit.normal[2] as f32);
+        Highlight { min: center - half, max: center + half, normal }
     }
-    
 
-    fn place_overhang_roof(
-        cubes: &mut Vec<Cube>, center: Vec3, cube_size: f32, spacing: f32, offset: f32
-    ) {
-        for x in Self::ROOF_X0..Self::ROOF_X0 + Self::ROOF_W {
-            for z in Self::ROOF_Z0..Self::ROOF_Z0 + Self::ROOF_H {
-                let pos = Vec3::new(
-                    center.x + (x as f32) * spacing - offset,
-                    center.y + (Self::ROOF_Y as f32) * spacing,
-                    center.z + (z as f32) * spacing - offset,
-                );
-                cubes.push(Cube::new(pos, cube_size, Material::stone_layer()));
+    // árboles, arbustos, flores y cactus repartidos con disco de Poisson según el bioma
+    fn place_vegetation(world: &mut World, terrain: &Terrain) {
+        let size = terrain.size() as i32;
+        for plant in vegetation::scatter(terrain, terrain.config.vegetation_density, terrain.config.seed) {
+            for block in &plant.blocks {
+                let x = plant.x as i32 + block.offset.0;
+                let y = plant.y as i32 + block.offset.1;
+                let z = plant.z as i32 + block.offset.2;
+                // las copas no salen del diorama ni se meten en el terreno ni en otra planta
+                if x < 0 || z < 0 || x >= size || z >= size || !world.get(x, y, z).is_air() {
+                    continue;
+                }
+                world.set_named(x, y, z, block.name);
             }
         }
     }
 
-
-    
-        
-    // determinar el material , AGUa, lava, etc para cada scene
-    fn determine_material(x: usize, z: usize, y_level: usize, max_height: usize) -> Material {
-        let lava_zone = x < 6;
-        let sand_zone = x >= 6 && x < 12;
-        let grass_zone = x >= 12;
-        let forest_zone = x >= 12;
-
-        if lava_zone {
-            
-            if y_level == 1 {
-                return Material::lava_surface();
-            }
-
-          // para la lava 
-            if Self::in_any_lava_pond(x, z, 18) && y_level == max_height {
-                return Material::lava_surface();
+    // cristales en el piso de las cuevas volcánicas; si no hay cuevas, sobre el terreno
+    fn place_crystal_details(world: &mut World, terrain: &Terrain) {
+        let mut spots = terrain.cave_floors(Biome::Volcanic, 2);
+        if spots.is_empty() {
+            spots = terrain.dry_columns(Biome::Volcanic).into_iter()
+                .map(|(x, z)| (x, terrain.height(x, z) + 1, z))
+                .collect();
+        }
+        for fraction in [0.3, 0.7] {
+            if spots.is_empty() {
+                return;
             }
-
-            // obsidiana
-
-            if (x <= 2 || z <= 2) && ((x + 2*z + y_level) % 5 == 0 || (3*x + z) % 7 == 0) {
-                return Material::obsidian_block();                         
+            let index = ((spots.len() as f32 * fraction) as usize).min(spots.len() - 1);
+            let (cx, base_y, cz) = spots[index];
+            for y in base_y..=base_y + 1 {
+                world.set_named(cx as i32, y as i32, cz as i32, "crystal");
             }
-
-            return Material::stone_layer();
         }
+    }
 
-        if sand_zone {
-            //  oasis superficie
-            if Self::in_oasis(x, z) && y_level == 1 || y_level == 2 {
-                return Material::water_surface();
-            }
-            // para la sand
-            if y_level == max_height { return Material::sand_top(); }
-            return Material::stone_layer();
-        }
+    // el fluido llena desde encima de la columna hasta el nivel del mar; `layout` es el de `layout()`
+    fn fluid_surface_for(x: usize, z: usize, top_level: usize, bottom_level: usize, material: Material, waves: Vec<Wave>,
+                         (center, cube_size, offset): (Vec3, f32, f32)) -> FluidSurface {
+        let half = cube_size / 2.0;
+        let cx = center.x + x as f32 * cube_size - offset;
+        let cz = center.z + z as f32 * cube_size - offset;
+        let top_block = center.y + top_level as f32 * cube_size - half;
+        let bottom = center.y + bottom_level as f32 * cube_size - half;
+        FluidSurface::new(
+            (cx - half, cz - half),
+            (cx + half, cz + half),
+            top_block + FLUID_LEVEL * cube_size,
+            bottom,
+            cube_size,
+            material,
+            waves,
+        )
+    }
 
-        if grass_zone {
-            // grama
-            if y_level == max_height { return Material::grass_top(); }
-            if y_level >= max_height - 1 { return Material::dirt_layer(); }
-            return Material::stone_layer();
+    fn add_fluid_areas(water_surfaces: &mut Vec<FluidSurface>, lava_surfaces: &mut Vec<FluidSurface>, terrain: &Terrain,
+                       layout: (Vec3, f32, f32)) {
+        for z in 0..terrain.size() {
+            for x in 0..terrain.size() {
+                let Some((level, material)) = terrain.fluid(x, z) else { continue };
+                let bottom = terrain.height(x, z) + 1;
+                if material.material_type == MaterialType::Lava {
+                    lava_surfaces.push(Self::fluid_surface_for(
+                        x, z, level, bottom, material, Wave::lava_waves(), layout,
+                    ));
+                } else {
+                    water_surfaces.push(Self::fluid_surface_for(
+                        x, z, level, bottom, material, Wave::water_waves(), layout,
+                    ));
+                }
+            }
         }
 
-        if forest_zone {
-            
-            if y_level == max_height { return Material::grass_top(); }
-            if y_level >= max_height - 1 { return Material::dirt_layer(); }
-            return Material::stone_layer();
+        // lagos de lava de un bloque en el fondo de las cuevas
+        for (x, y, z) in terrain.cave_pools() {
+            lava_surfaces.push(Self::fluid_surface_for(
+                x, z, y, y, Material::lava_surface(), Wave::lava_waves(), layout,
+            ));
         }
-
-        
-        Material::stone_layer()
     }
 
-    
-    fn should_place_cube(x: usize, z: usize, y_level: usize, max_height: usize, grid_size: usize) -> bool {
-       
-
-        if x < 6 {
-           
-           if y_level == 1 { return true; }
-           // huecoo
-            
-
-
-            return true; 
-        }
-
-
-        // Para el oasis 
-        if x >= 6 && x < 12 && Self::in_oasis(x, z) {
-            if y_level == 1  || y_level == 2  { return true; } 
-            if y_level >= 2 { return false; }
+    // las cascadas son bloques de agua que caen por la columna
+    fn place_waterfalls(world: &mut World, terrain: &Terrain) {
+        for waterfall in terrain.waterfalls() {
+            for y in waterfall.bottom..=waterfall.top {
+                world.set_named(waterfall.x as i32, y as i32, waterfall.z as i32, "water");
+            }
         }
-
-        // para el forest - lo quité porque no me gustó 
-        //if x >= 12 && Self::in_grotto_cut(x, z) && (y_level == 6 || y_level == 5) {
-          //  return false;
-        //}
-
-        true
-    }
-    
-    fn add_water_areas(_water_planes: &mut Vec<Plane>, _heights: &Vec<Vec<usize>>, _center: Vec3, _cube_size: f32, _spacing: f32, _offset: f32) {
-    }
-    
-    fn add_lava_areas(_lava_planes: &mut Vec<Plane>, _heights: &Vec<Vec<usize>>, _center: Vec3, _cube_size: f32, _spacing: f32, _offset: f32) {
     }
 
-
-    
-    
-
-    
-    pub fn ray_intersect_fast(&self, ray_origin: &Vec3, ray_direction: &Vec3) -> Option<(usize, f32, u8)> {
+    // devuelve (grupo, índice, distancia, tipo); el grupo es el chunk de los cubos y quads, la malla de los
+    // triángulos y 0 para las superficies.
+    // tipo de objeto: 1 = cubo, 2 = superficie de agua, 3 = superficie de lava, 4 = quad, 6 = triángulo de un modelo
+    pub fn ray_intersect_fast(&self, ray_origin: &Vec3, ray_direction: &Vec3, time: f32) -> Option<(usize, usize, f32, u8)> {
         if !self.ray_intersects_bbox(ray_origin, ray_direction) {
             return None;
         }
@@ -838,17 +664,54 @@ impl OptimizedDiorama {
         let mut closest_distance = f32::INFINITY;
         let mut closest_index = None;
         
-        for (i, cube) in self.cubes.iter().enumerate() {
-            if let Some(distance) = cube.ray_intersect(ray_origin, ray_direction) {
-                if distance > 0.001 && distance < closest_distance {
-                    closest_distance = distance;
-                    closest_index = Some(i);
-                    if distance < 0.1 { break; }
+        'cubes: for (slot, chunk) in self.chunk_meshes.iter().enumerate() {
+            for (i, cube) in chunk.cubes.iter().enumerate() {
+                if let Some(distance) = cube.ray_intersect(ray_origin, ray_direction) {
+                    if distance > 0.001 && distance < closest_distance {
+                        closest_distance = distance;
+                        closest_index = Some((slot, i));
+                        if distance < 0.1 { break 'cubes; }
+                    }
                 }
             }
         }
         
-        closest_index.map(|idx| (idx, closest_distance, 1))
+        let mut closest = closest_index.map(|(slot, idx)| (slot, idx, closest_distance, 1));
+
+        for (slot, chunk) in self.chunk_meshes.iter().enumerate() {
+            for (i, quad) in chunk.quads.iter().enumerate() {
+                if let Some(distance) = quad.ray_intersect(ray_origin, ray_direction) {
+                    if distance < closest_distance {
+                        closest_distance = distance;
+                        closest = Some((slot, i, distance, 4));
+                    }
+                }
+            }
+        }
+
+        let visible = |_: &Triangle, distance: f32| self.clip.contains(&(ray_origin + ray_direction * distance));
+        for (m, mesh) in self.meshes.iter().enumerate() {
+            if let Some((i, distance)) = mesh.ray_intersect(ray_origin, ray_direction, closest_distance, visible) {
+                closest_distance = distance;
+                closest = Some((m, i, distance, 6));
+            }
+        }
+
+        for (object_type, surfaces) in [(2, &self.water_surfaces), (3, &self.lava_surfaces)] {
+            for (i, surface) in surfaces.iter().enumerate() {
+                if let Some(distance) = surface.ray_intersect(ray_origin, ray_direction, time) {
+                    if !self.clip.contains(&(ray_origin + ray_direction * distance)) {
+                        continue;
+                    }
+                    if distance > 0.001 && distance < closest_distance {
+                        closest_distance = distance;
+                        closest = Some((0, i, distance, object_type));
+                    }
+                }
+            }
+        }
+
+        closest
     }
     
     fn ray_intersects_bbox(&self, ray_origin: &Vec3, ray_direction: &Vec3) -> bool {
@@ -879,8 +742,48 @@ impl OptimizedDiorama {
         t_max > 0.0
     }
     
+    // el bloque de un cubo del render, y el vecino del otro lado de la cara con esa normal
+    fn cube_block(&self, cube: &Cube, face_normal: &Vec3) -> ([i32; 3], [i32; 3]) {
+        let center = self.to_block_space(&((cube.min + cube.max) * 0.5));
+        let block = [center.x.floor() as i32, center.y.floor() as i32, center.z.floor() as i32];
+        let neighbor = [0, 1, 2].map(|i| block[i] + face_normal[i].round() as i32);
+        (block, neighbor)
+    }
+
+    // material del bloque pegado al cubo del otro lado de la cara con esa normal; si el corte lo sacó,
+    // del otro lado no hay nada (el rayo sale del medio en la cara de corte)
+    fn material_beyond(&self, cube: &Cube, face_normal: &Vec3) -> Option<Material> {
+        let (_, [x, y, z]) = self.cube_block(cube, face_normal);
+        if !self.clip.contains(&self.block_position(x, y, z)) {
+            return None;
+        }
+        Some(self.world.registry.get(self.world.get(x, y, z)).material)
+    }
+
+    // cara entre dos bloques transparentes iguales que se dibujan los dos (agua con agua, vidrio con vidrio):
+    // desde afuera no se ve
+    pub fn is_inner_face(&self, cube: &Cube, face_normal: &Vec3) -> bool {
+        let ([x, y, z], [nx, ny, nz]) = self.cube_block(cube, face_normal);
+        let id = self.world.get(x, y, z);
+        self.world.registry.get(id).material.is_transparent()
+            && self.world.get(nx, ny, nz) == id
+            && self.clip.contains(&self.block_position(nx, ny, nz))
+    }
+
     pub fn ray_intersect_shadow_fast(&self, ray_origin: &Vec3, ray_direction: &Vec3, max_distance: f32) -> bool {
-        for (i, cube) in self.cubes.iter().enumerate() {
+        // los quads se prueban todos: cada uno tapa varios bloques
+        let blocked = self.quads().any(|quad| {
+            quad.ray_intersect(ray_origin, ray_direction).is_some_and(|distance| distance < max_distance)
+        });
+        // los triángulos transparentes dejan pasar la luz, y los cortados no están
+        let casts_shadow = |triangle: &Triangle, distance: f32| {
+            !triangle.material.is_transparent() && self.clip.contains(&(ray_origin + ray_direction * distance))
+        };
+        let blocked = blocked || self.meshes.iter().any(|mesh| mesh.ray_intersect(ray_origin, ray_direction, max_distance, casts_shadow).is_some());
+        if blocked {
+            return true;
+        }
+        for (i, cube) in self.cubes().enumerate() {
             if i % 2 == 0 {
                 if let Some(distance) = cube.ray_intersect(ray_origin, ray_direction) {
                     if distance > 0.001 && distance < max_distance {
@@ -893,31 +796,43 @@ impl OptimizedDiorama {
     }
 }
 
+// cara de corte: más oscura y con el borde de cada bloque marcado, como un plano de sección
+fn section_color(color: Color, (u, v): (f32, f32)) -> Color {
+    let edge = u.min(1.0 - u).min(v).min(1.0 - v) < 0.04;
+    Color::from_vec3(color.to_vec3() * if edge { 0.35 } else { 0.75 })
+}
+
+// cara apuntada con el mouse: más clara y con el borde en blanco
+fn highlight_color(color: Color, (u, v): (f32, f32)) -> Color {
+    if u.min(1.0 - u).min(v).min(1.0 - v) < 0.05 {
+        return Color::white();
+    }
+    Color::from_vec3(color.to_vec3() * 1.3)
+}
+
 fn reflect(dir: &Vec3, normal: &Vec3) -> Vec3 { *dir - *normal * 2.0 * dot(dir, normal) }
 
-fn refract(incident: &Vec3, normal: &Vec3, eta: f32) -> Option<Vec3> {
+// n1 = IOR del lado de donde viene el rayo, n2 = del lado al que pasa
+fn refract(incident: &Vec3, normal: &Vec3, n1: f32, n2: f32) -> Option<Vec3> {
     let mut n = *normal;
     let mut cosi = dot(incident, &n).clamp(-1.0, 1.0);
-    let mut etai = 1.0;
-    let mut etat = eta;
-    if cosi < 0.0 { cosi = -cosi; } else { std::mem::swap(&mut etai, &mut etat); n = -n; }
-    let eta_ratio = etai / etat;
+    if cosi < 0.0 { cosi = -cosi; } else { n = -n; }
+    let eta_ratio = n1 / n2;
     let k = 1.0 - eta_ratio * eta_ratio * (1.0 - cosi * cosi);
     if k < 0.0 { None } else { Some(*incident * eta_ratio + n * (eta_ratio * cosi - k.sqrt())) }
 }
 
-fn fresnel(incident: &Vec3, normal: &Vec3, ior: f32) -> f32 {
-    let mut cosi = dot(incident, normal).clamp(-1.0, 1.0);
-    let etai = 1.0;
-    let etat = ior;
-    if cosi > 0.0 {
-        let r0 = ((etat - etai) / (etat + etai)).powi(2);
-        r0 + (1.0 - r0) * (1.0 - cosi).powi(5)
-    } else {
-        let cosi_abs = -cosi;
-        let r0 = ((etat - etai) / (etat + etai)).powi(2);
-        r0 + (1.0 - r0) * (1.0 - cosi_abs).powi(5)
+// Fresnel dieléctrico (promedio s/p); da 1.0 justo cuando refract no encuentra dirección
+fn fresnel(incident: &Vec3, normal: &Vec3, n1: f32, n2: f32) -> f32 {
+    let cosi = dot(incident, normal).clamp(-1.0, 1.0).abs();
+    let sint = n1 / n2 * (1.0 - cosi * cosi).max(0.0).sqrt();
+    if sint >= 1.0 {
+        return 1.0;
     }
+    let cost = (1.0 - sint * sint).max(0.0).sqrt();
+    let rs = ((n1 * cosi - n2 * cost) / (n1 * cosi + n2 * cost)).powi(2);
+    let rp = ((n1 * cost - n2 * cosi) / (n1 * cost + n2 * cosi)).powi(2);
+    (rs + rp) / 2.0
 }
 
 fn sample_sky(skybox: &Option<Skybox>, dir: &Vec3) -> Color {
@@ -935,9 +850,9 @@ fn sample_sky(skybox: &Option<Skybox>, dir: &Vec3) -> Color {
 }
 
 fn cast_ray_optimized_recursive(ray_origin: &Vec3, ray_direction: &Vec3, diorama: &OptimizedDiorama, floor: &Plane, 
-                                lights: &[Light], grass_texture: &Texture, dirt_texture: &Texture, stone_texture: &Texture, 
-                                water_texture: &Texture, lava_texture: &Texture, obsidian_texture: &Texture,   sand_texture: &Texture, leaves_texture: &Texture, wood_texture: &Texture,   crystal_texture: &Texture,  cactus_texture: &Texture,   
-                                skybox: &Option<Skybox>, stats: &mut RenderStats, depth: u32) -> Color {
+                                lights: &[Light], textures: &BlockTextures, settings: &RenderSettings,
+                                skybox: &Option<Skybox>, stats: &mut RenderStats, depth: u32,
+                                medium: MediumStack, wavelength: Option<f32>) -> Color {
     if depth == 0 {
         return sample_sky(skybox, ray_direction);
     }
@@ -948,19 +863,82 @@ fn cast_ray_optimized_recursive(ray_origin: &Vec3, ray_direction: &Vec3, diorama
     let mut hit_normal = Vec3::new(0.0, 0.0, 0.0);
     let mut hit_object = 0;
     let mut hit_cube: Option<&Cube> = None;
+    let mut hit_quad: Option<&Quad> = None;
+    let mut hit_surface: Option<&FluidSurface> = None;
+    let mut hit_triangle: Option<&Triangle> = None;
 
     stats.rays_cast += 1;
 
-    if let Some((object_index, distance, object_type)) = diorama.ray_intersect_fast(ray_origin, ray_direction) {
+    // entre dos bloques del mismo medio (agua junto a agua) no hay interfaz real, el rayo sigue recto;
+    // se avanza en un bucle y las distancias se suman para la absorción
+    let mut origin = *ray_origin;
+    let mut travelled = 0.0;
+    let fast_hit = loop {
+        let hit = diorama.ray_intersect_fast(&origin, ray_direction, settings.time);
+        let Some((slot, object_index, distance, 1)) = hit else { break hit };
+        let cube = &diorama.chunk_meshes[slot].cubes[object_index];
+        let point = origin + ray_direction * distance;
+        let normal = cube.get_normal(&point);
+        if distance <= 0.001 || !cube.material.is_transparent() {
+            break hit;
+        }
+        let same_medium = if dot(ray_direction, &normal) < 0.0 {
+            medium.is_inside(cube.material.material_type)
+        } else {
+            diorama.material_beyond(cube, &normal).is_some_and(|m| m.material_type == cube.material.material_type)
+        };
+        if !same_medium {
+            break hit;
+        }
+        travelled += distance + 0.001;
+        origin = point + ray_direction * 0.001;
+    };
+
+    if let Some((slot, object_index, distance, object_type)) = fast_hit.map(|(g, i, distance, t)| (g, i, travelled + distance, t)) {
         if distance > 0.001 && distance < closest_distance && object_type == 1 {
             closest_distance = distance;
-            let cube = &diorama.cubes[object_index];
+            let cube = &diorama.chunk_meshes[slot].cubes[object_index];
             hit_material = Some(cube.material);
             hit_point = ray_origin + ray_direction * distance;
             hit_normal = cube.get_normal(&hit_point);
             hit_cube = Some(cube);
             hit_object = 1;
             stats.hits += 1;
+        } else if distance > 0.001 && distance < closest_distance && object_type == 4 {
+            closest_distance = distance;
+            let quad = &diorama.chunk_meshes[slot].quads[object_index];
+            hit_material = Some(quad.material);
+            hit_point = ray_origin + ray_direction * distance;
+            hit_normal = quad.normal;
+            hit_quad = Some(quad);
+            hit_object = 4;
+            stats.hits += 1;
+        } else if distance > 0.001 && distance < closest_distance && (object_type == 2 || object_type == 3) {
+            closest_distance = distance;
+            let surface = if object_type == 2 {
+                &diorama.water_surfaces[object_index]
+            } else {
+                &diorama.lava_surfaces[object_index]
+            };
+            hit_material = Some(surface.material);
+            hit_point = ray_origin + ray_direction * distance;
+            hit_normal = surface.get_normal(&hit_point, settings.time);
+            hit_surface = Some(surface);
+            hit_object = object_type;
+            stats.hits += 1;
+        } else if distance > 0.001 && distance < closest_distance && object_type == 6 {
+            closest_distance = distance;
+            let triangle = &diorama.meshes[slot].triangles[object_index];
+            hit_material = Some(triangle.material);
+            hit_point = ray_origin + ray_direction * distance;
+            hit_normal = triangle.get_normal();
+            // lo opaco se ve igual de los dos lados (las mallas pueden no estar cerradas)
+            if !triangle.material.is_transparent() && dot(ray_direction, &hit_normal) > 0.0 {
+                hit_normal = -hit_normal;
+            }
+            hit_triangle = Some(triangle);
+            hit_object = 6;
+            stats.hits += 1;
         }
     }
 
@@ -977,34 +955,78 @@ fn cast_ray_optimized_recursive(ray_origin: &Vec3, ray_direction: &Vec3, diorama
 
     if hit_object == 0 {
         stats.misses += 1;
-        return sample_sky(skybox, ray_direction);
+        return apply_absorption(sample_sky(skybox, ray_direction), &medium, travelled);
     }
 
     if let Some(material) = hit_material {
-        let base_color = if hit_object == 1 && material.has_texture && hit_cube.is_some() {
-            let cube = hit_cube.unwrap();
-            let (u, v) = cube.get_uv_coordinates(&hit_point);
-            match material.material_type {
-                MaterialType::Grass    => grass_texture.sample(u, v),
-                MaterialType::Dirt     => dirt_texture.sample(u, v),
-                MaterialType::Stone    => stone_texture.sample(u, v),
-                MaterialType::Water    => water_texture.sample(u, v),
-                MaterialType::Lava     => lava_texture.sample(u, v),
-                MaterialType::Obsidian => obsidian_texture.sample(u, v),
-                MaterialType::Sand     => sand_texture.sample(u, v),
-                MaterialType::Wood     => wood_texture.sample(u, v),
-                MaterialType::Leaves   => leaves_texture.sample(u, v),
-                MaterialType::Crystal => crystal_texture.sample(u, v),
-                MaterialType::Glass | MaterialType::Metal => material.diffuse,
-                MaterialType::Cactus   => cactus_texture.sample(u, v),
+        // normal de sombreado (normal map), la geométrica se sigue usando para los offsets
+        let mut shading_normal = hit_normal;
+        // en los modelos, la normal suave de los vértices, del mismo lado que la geométrica
+        if let Some(triangle) = hit_triangle {
+            let smooth = triangle.get_smooth_normal(&hit_point);
+            shading_normal = if dot(&smooth, &hit_normal) < 0.0 { -smooth } else { smooth };
+        }
+
+        // uv y tangentes de la cara del bloque, sea un cubo o un quad, o del triángulo del modelo
+        let face = hit_cube.map(|cube| (cube.get_uv_coordinates(&hit_point), cube.get_tangent_frame(&hit_point)))
+            .or_else(|| hit_quad.map(|quad| (quad.get_uv_coordinates(&hit_point), quad.get_tangent_frame())))
+            .or_else(|| hit_triangle.map(|triangle| (triangle.get_uv_coordinates(&hit_point), triangle.get_tangent_frame())));
+
+        let base_color = if material.has_texture && let Some(((mut u, mut v), (tangent, bitangent))) = face {
+
+            // parallax: desplaza el uv según la altura y el ángulo de vista
+            if material.parallax_scale > 0.0 && let Some(height_map) = textures.height_map(material.material_type) {
+                let view = -ray_direction;
+                let view_n = dot(&view, &hit_normal).max(0.05);
+                let depth_offset = (1.0 - height_map.sample_height(u, v)) * material.parallax_scale;
+                u = (u - dot(&view, &tangent) / view_n * depth_offset).clamp(0.0, 1.0);
+                v = (v - dot(&view, &bitangent) / view_n * depth_offset).clamp(0.0, 1.0);
+            }
 
+            if material.normal_map_strength > 0.0 && let Some(normal_map) = textures.normal_map(material.material_type) {
+                let n = normal_map.sample_normal(u, v);
+                // verde del normal map apunta hacia arriba de la imagen, o sea -bitangente
+                let perturbed = tangent * (n.x * material.normal_map_strength)
+                    - bitangent * (n.y * material.normal_map_strength)
+                    + hit_normal * n.z.max(0.0);
+                shading_normal = normalize(&perturbed);
+            }
 
-                _ => material.diffuse,
+            match material.material_type {
+                MaterialType::Glass | MaterialType::Metal => material.diffuse,
+                _ => textures.albedo(material.material_type)
+                    .map(|texture| texture.sample(u, v))
+                    .unwrap_or(material.diffuse),
             }
+        } else if let Some(surface) = hit_surface {
+            let (u, v) = surface.get_uv_coordinates(&hit_point);
+            textures.albedo(material.material_type)
+                .map(|texture| texture.sample(u, v))
+                .unwrap_or(material.diffuse)
         } else {
             material.diffuse
         };
 
+        // el corte, lo apuntado y la selección se marcan solo sobre los bloques
+        let block_face = face.filter(|_| hit_triangle.is_none());
+        let base_color = match block_face {
+            Some((uv, _)) if diorama.clip.is_cut_face(&hit_point, &hit_normal) => section_color(base_color, uv),
+            _ => base_color,
+        };
+        let base_color = match (block_face, settings.highlight) {
+            (Some((uv, _)), Some(highlight)) if highlight.contains(&hit_point, &hit_normal) => {
+                highlight_color(base_color, uv)
+            }
+            _ => base_color,
+        };
+        // las aristas de la selección, donde pasan sobre alguna cara
+        let base_color = match settings.selection {
+            Some((min, max)) if block_face.is_some() && picking::on_box_edge(&min, &max, &hit_point, diorama.cube_size * 0.06) => {
+                Color::new(255, 210, 40)
+            }
+            _ => base_color,
+        };
+
         let ambient_strength = match material.material_type {
             MaterialType::Grass => 0.5,
             MaterialType::Stone => 0.25,
@@ -1013,14 +1035,17 @@ fn cast_ray_optimized_recursive(ray_origin: &Vec3, ray_direction: &Vec3, diorama
             MaterialType::Lava => 0.8,
             // ambient_strength
             MaterialType::Sand => 0.45,
-            MaterialType::Leaves => 0.55,
+            MaterialType::Leaves | MaterialType::SpruceLeaves => 0.55,
             MaterialType::Obsidian => 0.2,
             _ => 0.3,
         };
 
-        let mut total_r = base_color.r as f32 * ambient_strength;
-        let mut total_g = base_color.g as f32 * ambient_strength;
-        let mut total_b = base_color.b as f32 * ambient_strength;
+        // los metales no tienen difuso, todo su color sale de la reflexión
+        let diffuse_weight = 1.0 - material.metalness;
+
+        let mut total_r = base_color.r as f32 * ambient_strength * diffuse_weight;
+        let mut total_g = base_color.g as f32 * ambient_strength * diffuse_weight;
+        let mut total_b = base_color.b as f32 * ambient_strength * diffuse_weight;
 
         if material.is_emissive() {
             let ec = material.emission_color();
@@ -1041,7 +1066,7 @@ fn cast_ray_optimized_recursive(ray_origin: &Vec3, ray_direction: &Vec3, diorama
             }
 
             if !in_shadow {
-                let diff = nalgebra_glm::dot(&hit_normal, &light_dir).max(0.0);
+                let diff = nalgebra_glm::dot(&shading_normal, &light_dir).max(0.0);
                 let attenuation = 1.0 / (1.0 + 0.015 * light_distance + 0.0008 * light_distance * light_distance);
 
                 let surface_multiplier = match material.material_type {
@@ -1052,16 +1077,22 @@ fn cast_ray_optimized_recursive(ray_origin: &Vec3, ray_direction: &Vec3, diorama
                     MaterialType::Lava => 0.3,
                      
                     MaterialType::Sand => 1.2,
-                    MaterialType::Leaves => 1.6,
+                    MaterialType::Leaves | MaterialType::SpruceLeaves => 1.6,
                     MaterialType::Obsidian => 1.1,
                     _ => 1.0,
                 };
 
-                let light_contribution = diff * light.intensity * attenuation * surface_multiplier;
+                let light_contribution = diff * light.intensity * attenuation * surface_multiplier * diffuse_weight;
 
                 total_r += base_color.r as f32 * light.color.r as f32 / 255.0 * light_contribution;
                 total_g += base_color.g as f32 * light.color.g as f32 / 255.0 * light_contribution;
                 total_b += base_color.b as f32 * light.color.b as f32 / 255.0 * light_contribution;
+
+                let highlight = brdf::specular_highlight(&material, &shading_normal, &-ray_direction, &light_dir)
+                    * light.intensity * attenuation;
+                total_r += light.color.r as f32 * highlight.x;
+                total_g += light.color.g as f32 * highlight.y;
+                total_b += light.color.b as f32 * highlight.z;
             }
         }
 
@@ -1071,58 +1102,137 @@ fn cast_ray_optimized_recursive(ray_origin: &Vec3, ray_direction: &Vec3, diorama
             total_b.min(255.0) as u8,
         );
 
+        // normal del lado por donde llega el rayo (puede venir desde dentro del bloque)
+        let entering = dot(ray_direction, &hit_normal) < 0.0;
+        let outward = if entering { hit_normal } else { -hit_normal };
+
         let mut reflect_color = Color::black();
         if material.is_reflective() {
-            let refl_dir = reflect(ray_direction, &hit_normal);
-            let refl_origin = hit_point + hit_normal * 0.001;
-            
-            reflect_color = cast_ray_optimized_recursive(
-                &refl_origin, &refl_dir, diorama, floor, lights,
-                grass_texture, dirt_texture, stone_texture,
-                water_texture, lava_texture, obsidian_texture,
-                sand_texture,  leaves_texture,     wood_texture, crystal_texture, cactus_texture,
-        
-                skybox, stats, depth - 1
-            );
+            let refl_origin = hit_point + outward * 0.001;
+
+            if material.roughness < brdf::MIRROR_ROUGHNESS {
+                let refl_dir = reflect(ray_direction, &shading_normal);
+                reflect_color = cast_ray_optimized_recursive(
+                    &refl_origin, &refl_dir, diorama, floor, lights, textures, settings,
+                    skybox, stats, depth - 1, medium, wavelength
+                );
+            } else {
+                // reflexión glossy: direcciones muestreadas con GGX según la rugosidad
+                let view = -ray_direction;
+                let samples = if depth == MAX_DEPTH { GLOSSY_SAMPLES } else { 1 };
+                let mut rng = Rng::from_point(&hit_point, depth);
+                let mut sum = Vec3::zeros();
+                let mut weight_sum = 0.0;
+
+                for _ in 0..samples {
+                    let half = brdf::sample_half_vector(&shading_normal, material.roughness, &mut rng);
+                    let refl_dir = reflect(ray_direction, &half);
+                    if dot(&refl_dir, &outward) <= 0.0 {
+                        continue;
+                    }
 
+                    // F = 1 aquí, el fresnel se aplica al mezclar
+                    let pdf = brdf::sample_pdf(&shading_normal, &view, &refl_dir, material.roughness);
+                    let weight = brdf::evaluate_specular(&shading_normal, &view, &refl_dir, Vec3::new(1.0, 1.0, 1.0), material.roughness).x / pdf.max(1e-4);
+
+                    let sample_color = cast_ray_optimized_recursive(
+                        &refl_origin, &refl_dir, diorama, floor, lights, textures, settings,
+                        skybox, stats, depth - 1, medium, wavelength
+                    );
+                    sum += sample_color.to_vec3() * weight;
+                    weight_sum += weight;
+                }
+
+                if weight_sum > 0.0 {
+                    reflect_color = Color::from_vec3(sum / weight_sum);
+                }
+            }
         }
 
+        // al entrar se apila el material; al salir se vuelve al medio que lo rodeaba
+        let refr_medium = if entering { medium.entered(material) } else { medium.exited(&material) };
+        // IOR de cada lado de la interfaz (n1 de donde viene, n2 a donde va)
+        let interface_iors = |lambda: Option<f32>| {
+            let inside = lambda.map_or(material.refractive_index, |l| material.ior_at(l));
+            if entering { (medium.ior(lambda), inside) } else { (inside, refr_medium.ior(lambda)) }
+        };
+
         let mut refract_color = Color::black();
         if material.is_transparent() {
-            if let Some(refr_dir) = refract(ray_direction, &hit_normal, material.refractive_index) {
-                let refr_origin = hit_point - hit_normal * 0.001;
-               
-               refract_color = cast_ray_optimized_recursive(
-                    &refr_origin, &refr_dir, diorama, floor, lights,
-                    grass_texture, dirt_texture, stone_texture,
-                    water_texture, lava_texture, obsidian_texture,
-                    sand_texture, leaves_texture,    wood_texture, crystal_texture,cactus_texture,
-                    skybox, stats, depth - 1
-                );
+            let refr_origin = hit_point - outward * 0.001;
+
+            if wavelength.is_none() && settings.spectral_dispersion && material.is_dispersive() {
+                // luz blanca: cada longitud de onda se dobla con su propio IOR
+                let mut sum = Vec3::zeros();
+                let mut response_sum = Vec3::zeros();
+                for i in 0..spectrum::SPECTRAL_SAMPLES {
+                    let lambda = spectrum::sample_wavelength(i);
+                    let response = spectrum::wavelength_to_rgb(lambda);
+                    response_sum += response;
+
+                    let (n1, n2) = interface_iors(Some(lambda));
+                    if let Some(refr_dir) = refract(ray_direction, &hit_normal, n1, n2) {
+                        let sample_color = cast_ray_optimized_recursive(
+                            &refr_origin, &refr_dir, diorama, floor, lights, textures, settings,
+                            skybox, stats, depth - 1, refr_medium, Some(lambda)
+                        );
+                        sum += sample_color.to_vec3().component_mul(&response);
+                    }
+                }
+                refract_color = Color::from_vec3(sum.component_div(&response_sum));
+            } else {
+                let (n1, n2) = interface_iors(wavelength);
+                if let Some(refr_dir) = refract(ray_direction, &hit_normal, n1, n2) {
+                   refract_color = cast_ray_optimized_recursive(
+                        &refr_origin, &refr_dir, diorama, floor, lights, textures, settings,
+                        skybox, stats, depth - 1, refr_medium, wavelength
+                    );
+                }
             }
         }
 
         if material.is_transparent() || material.is_reflective() {
-            let kr = fresnel(ray_direction, &hit_normal, material.refractive_index).clamp(0.0, 1.0);
             if material.is_transparent() {
+                let (n1, n2) = interface_iors(wavelength);
+                let kr = fresnel(ray_direction, &shading_normal, n1, n2).clamp(0.0, 1.0);
                 let t = material.albedo[1];
                 let reflected_part = reflect_color.to_vec3() * kr;
                 let refracted_part = refract_color.to_vec3() * (1.0 - kr) * t;
                 let base_part = final_color.to_vec3() * (1.0 - t);
                 let mixed = base_part + reflected_part + refracted_part;
-                return Color::from_vec3(mixed).clamp();
+                return apply_absorption(Color::from_vec3(mixed).clamp(), &medium, closest_distance);
             } else {
-                let mixed = final_color.to_vec3() * (1.0 - kr) + reflect_color.to_vec3() * kr;
-                return Color::from_vec3(mixed).clamp();
+                let base = base_color.to_vec3() / 255.0;
+                let f0 = brdf::base_reflectance(&material, &base);
+                let cos_view = dot(&shading_normal, &-ray_direction);
+                let f = brdf::fresnel_schlick_roughness(cos_view, f0, material.roughness);
+                let mixed = final_color.to_vec3().component_mul(&(Vec3::new(1.0, 1.0, 1.0) - f))
+                    + reflect_color.to_vec3().component_mul(&f);
+                return apply_absorption(Color::from_vec3(mixed).clamp(), &medium, closest_distance);
             }
         }
 
-        final_color.clamp()
+        apply_absorption(final_color.clamp(), &medium, closest_distance)
     } else {
         sample_sky(skybox, ray_direction)
     }
 }
 
+// Beer-Lambert: atenúa lo que se ve a través del medio según la distancia recorrida
+fn apply_absorption(color: Color, medium: &MediumStack, distance: f32) -> Color {
+    match medium.current() {
+        Some(m) if distance.is_finite() => {
+            let t = m.transmittance(distance);
+            Color::new(
+                (color.r as f32 * t[0]) as u8,
+                (color.g as f32 * t[1]) as u8,
+                (color.b as f32 * t[2]) as u8,
+            )
+        }
+        _ => color,
+    }
+}
+
 trait ColorVec3 {
     fn to_vec3(&self) -> Vec3;
     fn from_vec3(v: Vec3) -> Self;
@@ -1145,82 +1255,111 @@ impl ColorVec3 for Color {
     }
 }
 
+// el diorama sale entero del archivo (bloques, fluidos y ubicación); la semilla solo queda
+// para que G siga desde ahí
+fn load_scene(path: &str, terrain_config: &mut TerrainConfig) -> Result<(OptimizedDiorama, OrbitCamera, Vec<Light>), Box<dyn std::error::Error>> {
+    let saved = save::load(path)?;
+    let mut world = World::new(BlockRegistry::with_defaults());
+    let chunks = saved.chunks(&mut world)?;
+    world.replace_chunks(chunks);
+    let (center, cube_size, offset) = saved.layout;
+    let diorama = OptimizedDiorama::from_parts(world, saved.water_surfaces, saved.lava_surfaces, center, cube_size, offset);
+    terrain_config.seed = saved.seed;
+    Ok((diorama, saved.camera, saved.lights))
+}
+
 fn main() {
     let mut framebuffer = Framebuffer::new(WIDTH, HEIGHT);
 
-    let grass_texture = match Texture::load_from_file("grass.png") {
-        Ok(tex) => tex,
-        Err(_) => Texture::create_grass_texture()
-    };
-    let dirt_texture = match Texture::load_from_file("dirt.png") {
-        Ok(tex) => tex,
-        Err(_) => Texture::create_dirt_texture()
-    };
-    let stone_texture = match Texture::load_from_file("stone.png") {
-        Ok(tex) => tex,
-        Err(_) => Texture::create_stone_texture()
-    };
-    let water_texture = match Texture::load_from_file("water.png") {
-        Ok(tex) => tex,
-        Err(_) => Texture::create_water_texture()
-    };
-    let lava_texture = match Texture::load_from_file("lava.png") {
-        Ok(tex) => tex,
-        Err(_) => Texture::create_lava_texture()
-    };
-
-    let obsidian_texture = match Texture::load_from_file("obsidian.png") {
-        Ok(tex) => tex,
-        Err(_) => Texture::create_obsidian_texture()
-    };
-
-    let leaves_texture = match Texture::load_from_file("leaves.png") {
-        Ok(tex) => tex,
-        Err(_) => Texture::create_leaves_texture(), 
-    };
-
-    
-    let wood_texture = match Texture::load_from_file("wood.png") {
-        Ok(tex) => tex,
-        Err(_) => Texture::create_wood_texture(), 
-    };
-
-    let cactus_texture = match Texture::load_from_file("cactus.png") {
-        Ok(tex) => tex,
-        Err(_) => Texture::create_cactus_texture(),
-    };
-
-    let crystal_texture = match Texture::load_from_file("crystal.png") {
-        Ok(tex) => tex,
-        Err(_) => {
-            // fallback muy simple si no hay PNG
-            let mut t = Texture { width: 32, height: 32, data: vec![] };
-            for _ in 0..(32*32) { t.data.extend_from_slice(&[170, 210, 255]); }
-            t
-        }
-    };
-
-    let sand_texture   = Texture::create_sand_texture();
- 
-    
+    // --still-fluids deja la lava y el agua sin animar
+    let mut textures = BlockTextures::load(std::env::args().any(|arg| arg == "--still-fluids"));
 
     let skybox = Some(Skybox::create_procedural_sky());
 
     let mut camera = OrbitCamera::new(Vec3::new(0.0, 2.0, 0.0), 10.0);
     camera.orbit(0.8, 0.4);
 
-    let diorama = OptimizedDiorama::new(Vec3::new(0.0, 0.0, 0.0), 0.8);
+    // la semilla del terreno se puede pasar como primer argumento; las opciones van con --
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let option = |name: &str| args.iter().position(|arg| arg == name).and_then(|i| args.get(i + 1)).cloned();
+    let first_arg = args.first().filter(|arg| !arg.starts_with("--")).cloned();
+    let mut terrain_config = TerrainConfig::default();
+    if let Some(seed) = first_arg.as_ref().and_then(|arg| arg.parse().ok()) {
+        terrain_config.seed = seed;
+    }
+    let mut diorama = OptimizedDiorama::new(Vec3::new(0.0, 0.0, 0.0), 0.8, &terrain_config);
     let floor = Plane::new(Vec3::new(0.0, -2.0, 0.0), Vec3::new(0.0, 1.0, 0.0), Material::stone_wall());
 
-    let lights = vec![
+    let mut lights = vec![
         Light::new(Vec3::new(-4.0, 8.0, -2.0), Color::new(255, 220, 180), 1.1),
         Light::new(Vec3::new(6.0, 6.0, 3.0), Color::new(180, 200, 255), 0.7),
     ];
 
+    // o un mundo guardado, si el argumento no es un número
+    if let Some(path) = first_arg.filter(|arg| arg.parse::<u32>().is_err()) {
+        match load_scene(&path, &mut terrain_config) {
+            Ok((loaded, loaded_camera, loaded_lights)) => (diorama, camera, lights) = (loaded, loaded_camera, loaded_lights),
+            Err(err) => println!("No se pudo cargar {}: {}", path, err),
+        }
+    }
+    diorama.mesh_stats.print_summary();
+
+    // modelo de MagicaVoxel para poner con I: --vox archivo.vox [--vox-scale n]
+    let vox_model = option("--vox").and_then(|path| match vox::load(&path) {
+        Ok(file) => {
+            if file.models.len() > 1 {
+                println!("{} trae {} modelos, se usa el primero", path, file.models.len());
+            }
+            Some(file)
+        }
+        Err(err) => {
+            println!("No se pudo leer {}: {}", path, err);
+            None
+        }
+    });
+    let vox_scale = option("--vox-scale").and_then(|scale| scale.parse().ok()).unwrap_or(1);
+
+    // construcción de Minecraft para poner con P: --structure archivo.nbt|.schem [--block-map reglas.txt]
+    let structure = option("--structure").and_then(|path| match schematic::load(&path) {
+        Ok(structure) => Some(structure),
+        Err(err) => {
+            println!("No se pudo leer {}: {}", path, err);
+            None
+        }
+    });
+    let block_mapping = option("--block-map").map_or_else(|| Ok(BlockMapping::default()), |path| BlockMapping::load(&path))
+        .unwrap_or_else(|err| {
+            println!("No se pudo leer el mapeo de bloques: {}", err);
+            BlockMapping::default()
+        });
+
+    // modelo .obj para poner con N: --model archivo.obj [--model-height bloques]
+    let model = option("--model").and_then(|path| match obj::load(&path, &mut diorama.world.registry) {
+        Ok(mesh) => {
+            println!("{}: {} triángulos", path, mesh.triangles.len());
+            Some(mesh)
+        }
+        Err(err) => {
+            println!("No se pudo leer {}: {}", path, err);
+            None
+        }
+    });
+    let model_height = option("--model-height").and_then(|height| height.parse().ok()).unwrap_or(2.0);
+
     let mut window = Window::new("Belén Diorama", WIDTH, HEIGHT, WindowOptions::default()).unwrap();
     window.set_target_fps(30);
 
     let mut stats = RenderStats::new();
+    let mut settings = RenderSettings::default();
+    let start_time = std::time::Instant::now();
+
+    // bloque que pone el clic derecho, elegido con 1 a 9
+    let mut selected = 0;
+    let mut mouse_was_down = (false, false);
+    // herramientas de región: esquinas de la selección, lo copiado y el radio de esferas y cilindros
+    let mut corners: [Option<[i32; 3]>; 2] = [None, None];
+    let mut clipboard: Option<Clipboard> = None;
+    let mut brush_radius = 2;
 
     while window.is_open() && !window.is_key_down(Key::Escape) {
         let orbit_speed = if window.is_key_down(Key::LeftShift) { 0.1 } else { 0.05 };
@@ -1235,13 +1374,229 @@ fn main() {
             camera = OrbitCamera::new(Vec3::new(0.0, 2.0, 0.0), 10.0);
             camera.orbit(0.8, 0.4);
         }
+        if window.is_key_pressed(Key::D, KeyRepeat::No) {
+            settings.spectral_dispersion = !settings.spectral_dispersion;
+        }
+        if window.is_key_pressed(Key::G, KeyRepeat::No) {
+            terrain_config.seed = terrain_config.seed.wrapping_add(1);
+            let enabled = diorama.clip.enabled;
+            diorama = OptimizedDiorama::new(Vec3::new(0.0, 0.0, 0.0), 0.8, &terrain_config);
+            if enabled {
+                diorama.clip.enabled = true;
+                diorama.rebuild_mesh();
+            }
+            println!("Terreno generado con semilla {}", terrain_config.seed);
+            diorama.mesh_stats.print_summary();
+        }
+        // F5 guarda el mundo editado, F9 lo vuelve a cargar
+        if window.is_key_pressed(Key::F5, KeyRepeat::No) {
+            match save::save(save::DEFAULT_PATH, &diorama, terrain_config.seed, &camera, &lights) {
+                Ok(()) => println!("Mundo guardado en {}", save::DEFAULT_PATH),
+                Err(err) => println!("No se pudo guardar: {}", err),
+            }
+            // el archivo no lleva los modelos .obj puestos con N: al cargarlo no vuelven
+            if !diorama.meshes.is_empty() {
+                println!("Aviso: los {} modelos .obj puestos no se guardan", diorama.meshes.len());
+            }
+        }
+        if window.is_key_pressed(Key::F9, KeyRepeat::No) {
+            match load_scene(save::DEFAULT_PATH, &mut terrain_config) {
+                Ok((loaded, loaded_camera, loaded_lights)) => {
+                    (diorama, camera, lights) = (loaded, loaded_camera, loaded_lights);
+                    println!("Mundo cargado de {}", save::DEFAULT_PATH);
+                    diorama.mesh_stats.print_summary();
+                }
+                Err(err) => println!("No se pudo cargar {}: {}", save::DEFAULT_PATH, err),
+            }
+        }
+        // O exporta lo que se ve a diorama.obj (con .mtl) y diorama.gltf (con .bin), con las texturas en PNG
+        if window.is_key_pressed(Key::O, KeyRepeat::No) {
+            let exported = export::write_obj("diorama", &diorama, &textures)
+                .and_then(|_| export::write_gltf("diorama", &diorama, &textures));
+            match exported {
+                Ok(faces) => println!("Exportado a diorama.obj y diorama.gltf ({} caras)", faces),
+                Err(err) => println!("No se pudo exportar: {}", err),
+            }
+        }
+        // corte: C lo prende, X / Z / Y corren el plano de ese eje un bloque hacia adentro (con Shift, hacia afuera)
+        let mut clip_changed = false;
+        let ctrl = window.is_key_down(Key::LeftCtrl) || window.is_key_down(Key::RightCtrl);
+        if !ctrl && window.is_key_pressed(Key::C, KeyRepeat::No) {
+            diorama.clip.enabled = !diorama.clip.enabled;
+            clip_changed = true;
+        }
+        let step = if window.is_key_down(Key::LeftShift) { 1 } else { -1 };
+        for (key, axis) in [(Key::X, 0), (Key::Y, 1), (Key::Z, 2)] {
+            if !ctrl && window.is_key_pressed(key, KeyRepeat::No) {
+                diorama.clip.move_plane(axis, step);
+                clip_changed = true;
+            }
+        }
+        // los bloques que deja a la vista el corte no tenían cubo
+        if clip_changed {
+            diorama.rebuild_mesh();
+        }
+
+        // Ctrl+Z deshace la última edición; Ctrl+Y (o Ctrl+Shift+Z) la rehace
+        let shift = window.is_key_down(Key::LeftShift);
+        let z_pressed = ctrl && window.is_key_pressed(Key::Z, KeyRepeat::Yes);
+        let y_pressed = ctrl && window.is_key_pressed(Key::Y, KeyRepeat::Yes);
+        if z_pressed && !shift {
+            match diorama.undo() {
+                Some(edit) => println!("Deshacer: {} ({} bloques)", edit.name, edit.changes.len()),
+                None => println!("Nada para deshacer"),
+            }
+        } else if y_pressed || z_pressed {
+            match diorama.redo() {
+                Some(edit) => println!("Rehacer: {} ({} bloques)", edit.name, edit.changes.len()),
+                None => println!("Nada para rehacer"),
+            }
+        }
+
+        let number_keys = [Key::Key1, Key::Key2, Key::Key3, Key::Key4, Key::Key5, Key::Key6, Key::Key7, Key::Key8, Key::Key9];
+        for (i, key) in number_keys.into_iter().enumerate() {
+            if window.is_key_pressed(key, KeyRepeat::No) {
+                selected = i;
+                println!("Bloque seleccionado: {}", HOTBAR[selected]);
+            }
+        }
+
+        // el rayo del mouse sale igual que el del pixel bajo el cursor
+        let mouse_ray = window.get_mouse_pos(MouseMode::Discard).map(|(mx, my)| {
+            let screen_x = ((2.0 * mx) / WIDTH as f32 - 1.0) * (WIDTH as f32 / HEIGHT as f32);
+            let screen_y = -(2.0 * my) / HEIGHT as f32 + 1.0;
+            camera.get_ray_direction(screen_x, screen_y)
+        });
+        let mut hit = mouse_ray.and_then(|direction| diorama.pick(&camera.eye, &direction));
+        let block_id = diorama.world.registry.id(HOTBAR[selected]);
+        let mut edited = 0;
+
+        // clic izquierdo rompe, clic derecho pone el bloque elegido sobre la cara apuntada
+        let mouse_down = (window.get_mouse_down(MouseButton::Left), window.get_mouse_down(MouseButton::Right));
+        if let Some(target) = hit {
+            if mouse_down.0 && !mouse_was_down.0 {
+                edited += diorama.edit("romper", [(target.block, BlockId::AIR)]);
+            } else if mouse_down.1 && !mouse_was_down.1 {
+                let [x, y, z] = target.adjacent();
+                if diorama.world.get(x, y, z).is_air() && let Some(id) = block_id {
+                    edited += diorama.edit("poner", [([x, y, z], id)]);
+                }
+            }
+        }
+        mouse_was_down = mouse_down;
+
+        // Q / E marcan las esquinas de la selección en el bloque apuntado
+        for (i, key) in [Key::Q, Key::E].into_iter().enumerate() {
+            if window.is_key_pressed(key, KeyRepeat::No) && let Some(target) = hit {
+                corners[i] = Some(target.block);
+                println!("Esquina {}: {:?}", i + 1, target.block);
+            }
+        }
+        let selection = match corners {
+            [Some(a), Some(b)] => Some(Region::from_corners(a, b)),
+            _ => None,
+        };
+
+        let mut tool: Option<(&str, Vec<region::Placement>)> = None;
+        if let (Some(region), Some(id)) = (selection, block_id) {
+            if window.is_key_pressed(Key::F, KeyRepeat::No) {
+                tool = Some(("rellenar", region::fill(&region, id)));
+            }
+            if window.is_key_pressed(Key::H, KeyRepeat::No) {
+                tool = Some(("ahuecar", region::hollow(&region, id)));
+            }
+            if window.is_key_pressed(Key::B, KeyRepeat::No) {
+                tool = Some(("paredes", region::walls(&region, id)));
+            }
+            // R cambia el tipo de bloque apuntado por el elegido
+            if window.is_key_pressed(Key::R, KeyRepeat::No) && let Some(target) = hit {
+                let [x, y, z] = target.block;
+                tool = Some(("reemplazar", region::replace(&diorama.world, &region, diorama.world.get(x, y, z), id)));
+            }
+        }
+        if ctrl && window.is_key_pressed(Key::C, KeyRepeat::No) && let Some(region) = selection {
+            let copied = Clipboard::copy(&diorama.world, &region);
+            println!("Copiado: {:?} bloques", copied.size());
+            clipboard = Some(copied);
+        }
+        if let Some(copied) = &mut clipboard {
+            if window.is_key_pressed(Key::T, KeyRepeat::No) {
+                *copied = copied.rotate();
+                println!("Copiado girado: {:?} bloques", copied.size());
+            }
+            // M refleja en x, con Shift en z
+            if window.is_key_pressed(Key::M, KeyRepeat::No) {
+                *copied = copied.mirror(if shift { 2 } else { 0 });
+                println!("Copiado reflejado");
+            }
+            if ctrl && window.is_key_pressed(Key::V, KeyRepeat::No) && let Some(target) = hit {
+                tool = Some(("pegar", copied.paste(target.adjacent())));
+            }
+        }
+
+        // esfera (K) y cilindro (L) sobre la cara apuntada; con Shift, huecos. [ y ] cambian el radio
+        if window.is_key_pressed(Key::LeftBracket, KeyRepeat::No) || window.is_key_pressed(Key::RightBracket, KeyRepeat::No) {
+            brush_radius = if window.is_key_down(Key::LeftBracket) { (brush_radius - 1).max(1) } else { brush_radius + 1 };
+            println!("Radio: {}", brush_radius);
+        }
+        if let (Some(target), Some(id)) = (hit, block_id) {
+            if window.is_key_pressed(Key::K, KeyRepeat::No) {
+                tool = Some(("esfera", region::sphere(target.adjacent(), brush_radius, id, shift)));
+            }
+            if window.is_key_pressed(Key::L, KeyRepeat::No) {
+                tool = Some(("cilindro", region::cylinder(target.adjacent(), brush_radius, brush_radius * 2, id, shift)));
+            }
+        }
+
+        // I pone el modelo .vox parado sobre la cara apuntada, centrado en x y z
+        if window.is_key_pressed(Key::I, KeyRepeat::No) && let (Some(file), Some(target)) = (&vox_model, hit) {
+            let [sx, _, sz] = file.models[0].size;
+            let [x, y, z] = target.adjacent();
+            let offset = [x - sx * vox_scale / 2, y, z - sz * vox_scale / 2];
+            tool = Some(("importar .vox", file.placements(0, &mut diorama.world.registry, offset, vox_scale)));
+        }
+
+        // P pone la construcción de Minecraft de la misma forma
+        if window.is_key_pressed(Key::P, KeyRepeat::No) && let (Some(structure), Some(target)) = (&structure, hit) {
+            let [sx, _, sz] = structure.size;
+            let [x, y, z] = target.adjacent();
+            let (placements, unknown) = structure.placements(&block_mapping, &mut diorama.world.registry, [x - sx / 2, y, z - sz / 2]);
+            if !unknown.is_empty() {
+                println!("Sin regla (van como {}): {}", block_mapping.fallback, unknown.join(", "));
+            }
+            tool = Some(("importar estructura", placements));
+        }
+
+        // N pone el modelo .obj parado sobre la cara apuntada; Shift+N saca el último que se puso
+        if window.is_key_pressed(Key::N, KeyRepeat::No) {
+            if shift {
+                if diorama.remove_last_mesh() {
+                    println!("Modelo sacado");
+                }
+            } else if let (Some(model), Some(target)) = (&model, hit) {
+                diorama.place_mesh(model, target.adjacent(), model_height);
+                println!("Modelo puesto ({} en total)", diorama.meshes.len());
+            }
+        }
+
+        if let Some((name, placements)) = tool {
+            let count = diorama.edit(name, placements);
+            println!("{}: {} bloques", name, count);
+            edited += count;
+        }
+        // después de editar, el bloque apuntado puede ser otro
+        if edited > 0 {
+            hit = mouse_ray.and_then(|direction| diorama.pick(&camera.eye, &direction));
+        }
+        settings.selection = selection.map(|region| diorama.region_box(&region));
+        settings.highlight = hit.map(|hit| diorama.highlight(&hit));
+
+        settings.time = start_time.elapsed().as_secs_f32();
+        textures.update(settings.time);
 
         stats.reset();
        render_optimized_recursive(
-            &mut framebuffer, &diorama, &floor, &lights, &camera,
-            &grass_texture, &dirt_texture, &stone_texture, &water_texture,
-            &lava_texture, &obsidian_texture,
-            &sand_texture, &wood_texture, &leaves_texture,  &crystal_texture, &cactus_texture,
+            &mut framebuffer, &diorama, &floor, &lights, &camera, &textures, &settings,
             &skybox, &mut stats
         );
 
@@ -1251,11 +1606,7 @@ fn main() {
 
 fn render_optimized_recursive(
         framebuffer: &mut Framebuffer, diorama: &OptimizedDiorama, floor: &Plane,
-        lights: &[Light], camera: &OrbitCamera,
-        grass_texture: &Texture, dirt_texture: &Texture, stone_texture: &Texture,
-        water_texture: &Texture, lava_texture: &Texture, obsidian_texture: &Texture,
-        sand_texture: &Texture, wood_texture: &Texture, leaves_texture: &Texture,  
-        crystal_texture: &Texture, cactus_texture: &Texture,
+        lights: &[Light], camera: &OrbitCamera, textures: &BlockTextures, settings: &RenderSettings,
         skybox: &Option<Skybox>, stats: &mut RenderStats
     ) {
     
@@ -1274,11 +1625,8 @@ fn render_optimized_recursive(
             
             let ray_direction = camera.get_ray_direction(screen_x, screen_y);
             let pixel_color = cast_ray_optimized_recursive(
-                   &camera.eye, &ray_direction, diorama, floor, lights,
-                grass_texture, dirt_texture, stone_texture,
-                water_texture, lava_texture, obsidian_texture,
-                sand_texture, leaves_texture, wood_texture,   crystal_texture, cactus_texture,
-                skybox, stats, MAX_DEPTH
+                   &camera.eye, &ray_direction, diorama, floor, lights, textures, settings,
+                skybox, stats, MAX_DEPTH, MediumStack::air(), None
                 );
             
             framebuffer.set_current_color(pixel_color);
@@ -3,14 +3,37 @@ use crate::color::Color;
 #[derive(Debug, Clone, Copy)]
 pub struct Material {
     pub diffuse: Color,
-    pub specular: f32,
+    pub specular: f32,           // exponente Blinn-Phong
+    pub specular_color: Color,
+    pub specular_strength: f32,  // brillo de la luz directa, aparte de la reflexión
+    pub specular_model: SpecularModel,
     pub albedo: [f32; 2],
-    pub refractive_index: f32,
+    pub refractive_index: f32,   // IOR
+    pub roughness: f32,          // 0 = espejo, 1 = mate
+    pub metalness: f32,
+    pub absorption: [f32; 3],    // Beer-Lambert por unidad de distancia (r, g, b)
+    pub dispersion: Dispersion,
     pub has_texture: bool,
     pub material_type: MaterialType,
+    pub normal_map_strength: f32,  // 0 = cara plana
+    pub parallax_scale: f32,
 }
 
+// cómo cambia el IOR con la longitud de onda (en micrómetros dentro de las fórmulas)
 #[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Dispersion {
+    None,
+    Cauchy { a: f32, b: f32 },
+    Sellmeier { b: [f32; 3], c: [f32; 3] },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SpecularModel {
+    BlinnPhong,
+    Ggx,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum MaterialType {
     Grass,
     Dirt,
@@ -25,6 +48,14 @@ pub enum MaterialType {
     Leaves,
     Crystal,  
     Cactus,  
+    BirchLog,
+    SpruceLeaves,
+    Flower,
+    CoalOre,
+    IronOre,
+    GoldOre,
+    RedstoneOre,
+    DiamondOre,
 }
 
 impl Material {
@@ -32,10 +63,19 @@ impl Material {
         Material {
             diffuse,
             specular: 0.0,
+            specular_color: Color::white(),
+            specular_strength: 0.0,
+            specular_model: SpecularModel::BlinnPhong,
             albedo: [1.0, 0.0],
             refractive_index: 1.0,
+            roughness: 0.8,
+            metalness: 0.0,
+            absorption: [0.0, 0.0, 0.0],
+            dispersion: Dispersion::None,
             has_texture: false,
             material_type: MaterialType::Stone,
+            normal_map_strength: 0.0,
+            parallax_scale: 0.0,
         }
     }
     
@@ -43,10 +83,19 @@ impl Material {
         Material {
             diffuse,
             specular: 0.0,
+            specular_color: Color::white(),
+            specular_strength: 0.0,
+            specular_model: SpecularModel::BlinnPhong,
             albedo: [1.0, 0.0],
             refractive_index: 1.0,
+            roughness: 0.8,
+            metalness: 0.0,
+            absorption: [0.0, 0.0, 0.0],
+            dispersion: Dispersion::None,
             has_texture: true,
             material_type,
+            normal_map_strength: 0.0,
+            parallax_scale: 0.0,
         }
     }
     
@@ -54,10 +103,19 @@ impl Material {
         Material {
             diffuse: Color::new(50, 180, 50),
             specular: 8.0,
+            specular_color: Color::white(),
+            specular_strength: 0.05,
+            specular_model: SpecularModel::BlinnPhong,
             albedo: [0.85, 0.15],
             refractive_index: 1.0,
+            roughness: 0.9,
+            metalness: 0.0,
+            absorption: [0.0, 0.0, 0.0],
+            dispersion: Dispersion::None,
             has_texture: true,
             material_type: MaterialType::Grass,
+            normal_map_strength: 0.0,
+            parallax_scale: 0.0,
         }
     }
 
@@ -65,10 +123,19 @@ impl Material {
         Material {
             diffuse: Color::new(160, 100, 50),
             specular: 3.0,
+            specular_color: Color::white(),
+            specular_strength: 0.02,
+            specular_model: SpecularModel::BlinnPhong,
             albedo: [0.9, 0.1],
             refractive_index: 1.0,
+            roughness: 0.95,
+            metalness: 0.0,
+            absorption: [0.0, 0.0, 0.0],
+            dispersion: Dispersion::None,
             has_texture: true,
             material_type: MaterialType::Dirt,
+            normal_map_strength: 0.0,
+            parallax_scale: 0.0,
         }
     }
 
@@ -76,10 +143,19 @@ impl Material {
         Material {
             diffuse: Color::new(90, 90, 95),
             specular: 15.0,
+            specular_color: Color::white(),
+            specular_strength: 0.15,
+            specular_model: SpecularModel::BlinnPhong,
             albedo: [0.7, 0.3],
             refractive_index: 1.0,
+            roughness: 0.7,
+            metalness: 0.0,
+            absorption: [0.0, 0.0, 0.0],
+            dispersion: Dispersion::None,
             has_texture: true,
             material_type: MaterialType::Stone,
+            normal_map_strength: 1.0,
+            parallax_scale: 0.04,
         }
     }
     
@@ -88,21 +164,39 @@ impl Material {
             //  blue más bueno
             diffuse: Color::new(30, 110, 220),
             specular: 100.0,       
+            specular_color: Color::white(),
+            specular_strength: 0.8,
+            specular_model: SpecularModel::BlinnPhong,
             
-            albedo: [0.6, 0.4],    // 60% de  color, 40%  de transparencia 
+            albedo: [0.2, 0.8],    // poco color de superficie, el azul sale de la absorción
             refractive_index: 1.33,
+            roughness: 0.03,
+            metalness: 0.0,
+            absorption: [0.9, 0.3, 0.1],
+            dispersion: Dispersion::None,
             has_texture: true,
             material_type: MaterialType::Water,
+            normal_map_strength: 0.0,
+            parallax_scale: 0.0,
         }
     }
     pub fn lava_surface() -> Self {
         Material {
             diffuse: Color::new(255, 80, 0), // Naranja más intenso
             specular: 15.0,
+            specular_color: Color::new(255, 200, 120),
+            specular_strength: 0.2,
+            specular_model: SpecularModel::BlinnPhong,
             albedo: [0.8, 0.2], 
             refractive_index: 1.0,
+            roughness: 0.6,
+            metalness: 0.0,
+            absorption: [0.0, 0.0, 0.0],
+            dispersion: Dispersion::None,
             has_texture: true,
             material_type: MaterialType::Lava,
+            normal_map_strength: 0.0,
+            parallax_scale: 0.0,
         }
     }
     
@@ -111,10 +205,19 @@ impl Material {
         Material {
             diffuse: Color::new(20, 18, 30), // Negro-púrpura oscuro
             specular: 50.0, // Bastante reflectante
+            specular_color: Color::new(200, 180, 255),
+            specular_strength: 0.6,
+            specular_model: SpecularModel::BlinnPhong,
             albedo: [0.8, 0.2], 
-            refractive_index: 1.0,
+            refractive_index: 1.49,
+            roughness: 0.12,
+            metalness: 0.0,
+            absorption: [0.0, 0.0, 0.0],
+            dispersion: Dispersion::None,
             has_texture: true,
             material_type: MaterialType::Obsidian,
+            normal_map_strength: 0.7,
+            parallax_scale: 0.02,
         }
     }
 
@@ -122,10 +225,19 @@ impl Material {
         Material {
             diffuse: Color::new(105, 105, 105),
             specular: 5.0,
+            specular_color: Color::white(),
+            specular_strength: 0.05,
+            specular_model: SpecularModel::BlinnPhong,
             albedo: [0.9, 0.1],
             refractive_index: 1.0,
+            roughness: 0.85,
+            metalness: 0.0,
+            absorption: [0.0, 0.0, 0.0],
+            dispersion: Dispersion::None,
             has_texture: true,
             material_type: MaterialType::Stone,
+            normal_map_strength: 0.0,
+            parallax_scale: 0.0,
         }
     }
     
@@ -133,10 +245,19 @@ impl Material {
         Material {
             diffuse: Color::new(139, 90, 43),
             specular: 10.0,
+            specular_color: Color::white(),
+            specular_strength: 0.1,
+            specular_model: SpecularModel::BlinnPhong,
             albedo: [0.9, 0.1],
             refractive_index: 1.0,
+            roughness: 0.8,
+            metalness: 0.0,
+            absorption: [0.0, 0.0, 0.0],
+            dispersion: Dispersion::None,
             has_texture: true,
             material_type: MaterialType::Wood,
+            normal_map_strength: 0.8,
+            parallax_scale: 0.0,
         }
     }
 
@@ -147,8 +268,17 @@ impl Material {
             diffuse: Color::new(60, 160, 70),
             albedo: [0.9, 0.1],
             specular: 10.0,
+            specular_color: Color::white(),
+            specular_strength: 0.15,
+            specular_model: SpecularModel::BlinnPhong,
             refractive_index: 1.0,
+            roughness: 0.8,
+            metalness: 0.0,
+            absorption: [0.0, 0.0, 0.0],
+            dispersion: Dispersion::None,
             material_type: MaterialType::Leaves,
+            normal_map_strength: 0.0,
+            parallax_scale: 0.0,
             has_texture: true,
         }
     }
@@ -158,8 +288,17 @@ impl Material {
             diffuse: Color::new(140, 100, 60),
              albedo: [0.9, 0.1],
             specular: 10.0,
+            specular_color: Color::white(),
+            specular_strength: 0.1,
+            specular_model: SpecularModel::BlinnPhong,
             refractive_index: 1.0,
+            roughness: 0.8,
+            metalness: 0.0,
+            absorption: [0.0, 0.0, 0.0],
+            dispersion: Dispersion::None,
             material_type: MaterialType::Wood,
+            normal_map_strength: 0.8,
+            parallax_scale: 0.0,
             has_texture: true,
         }
     }
@@ -170,8 +309,17 @@ impl Material {
             diffuse: Color::new(235, 220, 170),
             albedo: [0.9, 0.1],
             specular: 10.0,
+            specular_color: Color::white(),
+            specular_strength: 0.05,
+            specular_model: SpecularModel::BlinnPhong,
             refractive_index: 1.0,
+            roughness: 0.95,
+            metalness: 0.0,
+            absorption: [0.0, 0.0, 0.0],
+            dispersion: Dispersion::None,
             material_type: MaterialType::Sand,
+            normal_map_strength: 0.0,
+            parallax_scale: 0.0,
             has_texture: true,
         }
     }
@@ -180,10 +328,19 @@ impl Material {
         Material {
             diffuse: Color::new(180, 220, 255), // azulito claro
             specular: 110.0,               
+            specular_color: Color::white(),
+            specular_strength: 0.9,
+            specular_model: SpecularModel::BlinnPhong,
             albedo: [0.2, 0.8],                 
             refractive_index: 1.45,            
+            roughness: 0.08,
+            metalness: 0.0,
+            absorption: [0.35, 0.12, 0.02],
+            dispersion: Dispersion::Cauchy { a: 1.43, b: 0.012 },
             has_texture: true,
             material_type: MaterialType::Crystal,
+            normal_map_strength: 0.0,
+            parallax_scale: 0.0,
         }
     }
 
@@ -192,10 +349,45 @@ impl Material {
         Material {
             diffuse: Color::new(255, 255, 255),
             specular: 125.0,
+            specular_color: Color::white(),
+            specular_strength: 1.0,
+            specular_model: SpecularModel::BlinnPhong,
             albedo: [0.1, 0.9],
             refractive_index: 1.5,
+            roughness: 0.0,
+            metalness: 0.0,
+            absorption: [0.05, 0.02, 0.05],
+            dispersion: Dispersion::Sellmeier {
+                // BK7
+                b: [1.039_612, 0.231_792_34, 1.010_469_5],
+                c: [0.006_000_699, 0.020_017_914, 103.560_65],
+            },
             has_texture: false,
             material_type: MaterialType::Glass,
+            normal_map_strength: 0.0,
+            parallax_scale: 0.0,
+        }
+    }
+
+    // vidrio de color (voxels semitransparentes importados): absorbe lo que no es de su color
+    pub fn tinted_glass(tint: Color) -> Self {
+        let absorb = |c: u8| (1.0 - c as f32 / 255.0) * 0.8;
+        Material {
+            diffuse: tint,
+            specular: 125.0,
+            specular_color: Color::white(),
+            specular_strength: 1.0,
+            specular_model: SpecularModel::BlinnPhong,
+            albedo: [0.1, 0.9],
+            refractive_index: 1.5,
+            roughness: 0.0,
+            metalness: 0.0,
+            absorption: [absorb(tint.r), absorb(tint.g), absorb(tint.b)],
+            dispersion: Dispersion::None,
+            has_texture: false,
+            material_type: MaterialType::Glass,
+            normal_map_strength: 0.0,
+            parallax_scale: 0.0,
         }
     }
 
@@ -203,10 +395,182 @@ impl Material {
         Self {
             diffuse: Color::new(80, 170, 80),
             specular: 5.0,
+            specular_color: Color::white(),
+            specular_strength: 0.1,
+            specular_model: SpecularModel::BlinnPhong,
             albedo: [0.95, 0.05],
             refractive_index: 1.0,
+            roughness: 0.7,
+            metalness: 0.0,
+            absorption: [0.0, 0.0, 0.0],
+            dispersion: Dispersion::None,
             has_texture: true,
             material_type: MaterialType::Cactus,
+            normal_map_strength: 0.0,
+            parallax_scale: 0.0,
+        }
+    }
+
+    // menas: piedra con manchas del mineral; las de metal y el diamante brillan más que la piedra
+    pub fn coal_ore() -> Self {
+        Material {
+            diffuse: Color::new(70, 70, 72),
+            specular: 15.0,
+            specular_color: Color::white(),
+            specular_strength: 0.1,
+            specular_model: SpecularModel::BlinnPhong,
+            albedo: [0.7, 0.3],
+            refractive_index: 1.0,
+            roughness: 0.7,
+            metalness: 0.0,
+            absorption: [0.0, 0.0, 0.0],
+            dispersion: Dispersion::None,
+            has_texture: true,
+            material_type: MaterialType::CoalOre,
+            normal_map_strength: 1.0,
+            parallax_scale: 0.0,
+        }
+    }
+
+    pub fn iron_ore() -> Self {
+        Material {
+            diffuse: Color::new(150, 125, 110),
+            specular: 25.0,
+            specular_color: Color::white(),
+            specular_strength: 0.3,
+            specular_model: SpecularModel::BlinnPhong,
+            albedo: [0.7, 0.3],
+            refractive_index: 1.0,
+            roughness: 0.7,
+            metalness: 0.0,
+            absorption: [0.0, 0.0, 0.0],
+            dispersion: Dispersion::None,
+            has_texture: true,
+            material_type: MaterialType::IronOre,
+            normal_map_strength: 1.0,
+            parallax_scale: 0.0,
+        }
+    }
+
+    pub fn gold_ore() -> Self {
+        Material {
+            diffuse: Color::new(190, 160, 70),
+            specular: 40.0,
+            specular_color: Color::white(),
+            specular_strength: 0.45,
+            specular_model: SpecularModel::BlinnPhong,
+            albedo: [0.7, 0.3],
+            refractive_index: 1.0,
+            roughness: 0.7,
+            metalness: 0.0,
+            absorption: [0.0, 0.0, 0.0],
+            dispersion: Dispersion::None,
+            has_texture: true,
+            material_type: MaterialType::GoldOre,
+            normal_map_strength: 1.0,
+            parallax_scale: 0.0,
+        }
+    }
+
+    pub fn redstone_ore() -> Self {
+        Material {
+            diffuse: Color::new(150, 40, 40),
+            specular: 25.0,
+            specular_color: Color::white(),
+            specular_strength: 0.3,
+            specular_model: SpecularModel::BlinnPhong,
+            albedo: [0.7, 0.3],
+            refractive_index: 1.0,
+            roughness: 0.7,
+            metalness: 0.0,
+            absorption: [0.0, 0.0, 0.0],
+            dispersion: Dispersion::None,
+            has_texture: true,
+            material_type: MaterialType::RedstoneOre,
+            normal_map_strength: 1.0,
+            parallax_scale: 0.0,
+        }
+    }
+
+    pub fn diamond_ore() -> Self {
+        Material {
+            diffuse: Color::new(100, 200, 210),
+            specular: 60.0,
+            specular_color: Color::white(),
+            specular_strength: 0.6,
+            specular_model: SpecularModel::BlinnPhong,
+            albedo: [0.7, 0.3],
+            refractive_index: 1.0,
+            roughness: 0.7,
+            metalness: 0.0,
+            absorption: [0.0, 0.0, 0.0],
+            dispersion: Dispersion::None,
+            has_texture: true,
+            material_type: MaterialType::DiamondOre,
+            normal_map_strength: 1.0,
+            parallax_scale: 0.0,
+        }
+    }
+
+    // tronco de abedul: corteza blanca con rayas oscuras
+    pub fn birch_log() -> Self {
+        Self {
+            diffuse: Color::new(215, 210, 195),
+            specular: 10.0,
+            specular_color: Color::white(),
+            specular_strength: 0.1,
+            specular_model: SpecularModel::BlinnPhong,
+            albedo: [0.9, 0.1],
+            refractive_index: 1.0,
+            roughness: 0.8,
+            metalness: 0.0,
+            absorption: [0.0, 0.0, 0.0],
+            dispersion: Dispersion::None,
+            has_texture: true,
+            material_type: MaterialType::BirchLog,
+            normal_map_strength: 0.0,
+            parallax_scale: 0.0,
+        }
+    }
+
+    pub fn spruce_leaves() -> Self {
+        Self {
+            diffuse: Color::new(40, 95, 60),
+            specular: 10.0,
+            specular_color: Color::white(),
+            specular_strength: 0.1,
+            specular_model: SpecularModel::BlinnPhong,
+            albedo: [0.9, 0.1],
+            refractive_index: 1.0,
+            roughness: 0.85,
+            metalness: 0.0,
+            absorption: [0.0, 0.0, 0.0],
+            dispersion: Dispersion::None,
+            has_texture: true,
+            material_type: MaterialType::SpruceLeaves,
+            normal_map_strength: 0.0,
+            parallax_scale: 0.0,
+        }
+    }
+
+    // flores sin textura, el color de los pétalos va en diffuse
+    pub fn flower(petals: Color) -> Self {
+        Self {
+            diffuse: petals,
+            specular: 8.0,
+            specular_color: Color::white(),
+            specular_strength: 0.2,
+            specular_model: SpecularModel::BlinnPhong,
+            albedo: [0.95, 0.05],
+            refractive_index: 1.0,
+            roughness: 0.7,
+            metalness: 0.0,
+            absorption: [0.0, 0.0, 0.0],
+            dispersion: Dispersion::None,
+            has_texture: false,
+            material_type: MaterialType::Flower,
+            normal_map_strength: 0.0,
+            parallax_scale: 0.0,
         }
     }
 
@@ -216,10 +580,19 @@ impl Material {
         Material {
             diffuse: Color::new(192, 192, 192),
             specular: 100.0,
+            specular_color: Color::new(192, 192, 192),
+            specular_strength: 1.0,
+            specular_model: SpecularModel::Ggx,
             albedo: [0.4, 0.6],
             refractive_index: 1.0,
+            roughness: 0.25,
+            metalness: 1.0,
+            absorption: [0.0, 0.0, 0.0],
+            dispersion: Dispersion::None,
             has_texture: false,
             material_type: MaterialType::Metal,
+            normal_map_strength: 0.0,
+            parallax_scale: 0.0,
         }
     }
     
@@ -228,11 +601,39 @@ impl Material {
     }
     
     pub fn is_transparent(&self) -> bool {
-        matches!(self.material_type, MaterialType::Glass | MaterialType::Water)
+        matches!(self.material_type, MaterialType::Glass | MaterialType::Water | MaterialType::Crystal)
+    }
+
+    pub fn is_dispersive(&self) -> bool {
+        self.dispersion != Dispersion::None
+    }
+
+    // IOR para una longitud de onda en nm; sin dispersión es el refractive_index de siempre
+    pub fn ior_at(&self, wavelength_nm: f32) -> f32 {
+        let l = wavelength_nm / 1000.0;
+        match self.dispersion {
+            Dispersion::None => self.refractive_index,
+            Dispersion::Cauchy { a, b } => a + b / (l * l),
+            Dispersion::Sellmeier { b, c } => {
+                let l2 = l * l;
+                let sum: f32 = (0..3).map(|i| b[i] * l2 / (l2 - c[i])).sum();
+                (1.0 + sum).sqrt()
+            }
+        }
+    }
+
+    // cuánta luz sobrevive después de recorrer `distance` dentro del material
+    pub fn transmittance(&self, distance: f32) -> [f32; 3] {
+        [
+            (-self.absorption[0] * distance).exp(),
+            (-self.absorption[1] * distance).exp(),
+            (-self.absorption[2] * distance).exp(),
+        ]
     }
     
+    // metales y superficies pulidas; lo mate solo refleja vía la luz directa
     pub fn is_reflective(&self) -> bool {
-        self.specular > 50.0 || matches!(self.material_type, MaterialType::Water | MaterialType::Metal | MaterialType::Obsidian)
+        self.metalness > 0.0 || self.roughness < 0.5
     }
     
     pub fn emission_intensity(&self) -> f32 {
@@ -0,0 +1,59 @@
+use crate::material::{Material, MaterialType};
+
+// cuántos medios anidados se recuerdan (vidrio dentro de agua dentro de cristal...)
+pub const MAX_MEDIA: usize = 4;
+
+// pila de medios por los que viaja un rayo; vacía = aire
+#[derive(Debug, Clone, Copy)]
+pub struct MediumStack {
+    media: [Option<Material>; MAX_MEDIA],
+    len: usize,
+}
+
+impl MediumStack {
+    pub fn air() -> Self {
+        MediumStack { media: [None; MAX_MEDIA], len: 0 }
+    }
+
+    pub fn current(&self) -> Option<&Material> {
+        if self.len == 0 { None } else { self.media[self.len - 1].as_ref() }
+    }
+
+    pub fn is_inside(&self, material_type: MaterialType) -> bool {
+        self.current().is_some_and(|m| m.material_type == material_type)
+    }
+
+    // IOR del medio actual para la longitud de onda del rayo (si tiene)
+    pub fn ior(&self, wavelength: Option<f32>) -> f32 {
+        match (self.current(), wavelength) {
+            (Some(m), Some(lambda)) => m.ior_at(lambda),
+            (Some(m), None) => m.refractive_index,
+            (None, _) => 1.0,
+        }
+    }
+
+    pub fn entered(&self, material: Material) -> Self {
+        let mut next = *self;
+        if next.len < MAX_MEDIA {
+            next.media[next.len] = Some(material);
+            next.len += 1;
+        } else {
+            // sin espacio se reemplaza el de arriba, lo de afuera importa menos
+            next.media[MAX_MEDIA - 1] = Some(material);
+        }
+        next
+    }
+
+    // saca la entrada más reciente de ese material, aunque no esté arriba (medios que se cruzan)
+    pub fn exited(&self, material: &Material) -> Self {
+        let mut next = *self;
+        if let Some(i) = (0..next.len).rev().find(|&i| next.media[i].is_some_and(|m| m.material_type == material.material_type)) {
+            for j in i..next.len - 1 {
+                next.media[j] = next.media[j + 1];
+            }
+            next.len -= 1;
+            next.media[next.len] = None;
+        }
+        next
+    }
+}
@@ -0,0 +1,154 @@
+use crate::block::BlockId;
+use crate::cube::Cube;
+use crate::quad::Quad;
+use crate::world::World;
+use nalgebra_glm::Vec3;
+
+// la geometría de un chunk; al editar un bloque solo se rehacen los chunks que toca
+#[derive(Default)]
+pub struct ChunkMesh {
+    pub cubes: Vec<Cube>,
+    pub quads: Vec<Quad>,
+    pub visible_blocks: usize,
+    pub block_faces: usize,
+    pub bounds: Option<(Vec3, Vec3)>,
+}
+
+impl ChunkMesh {
+    pub fn new(cubes: Vec<Cube>, quads: Vec<Quad>, visible_blocks: usize, block_faces: usize) -> Self {
+        let bounds = cubes.iter().map(|c| (c.min, c.max))
+            .chain(quads.iter().map(|q| (q.min, q.max)))
+            .reduce(|(min_a, max_a), (min_b, max_b)| (nalgebra_glm::min2(&min_a, &min_b), nalgebra_glm::max2(&max_a, &max_b)));
+        ChunkMesh { cubes, quads, visible_blocks, block_faces, bounds }
+    }
+}
+
+// cara fusionada en coordenadas de esquina de bloque: el rectángulo va de `min` a `max`
+// y min[axis] == max[axis] es el plano de la cara
+pub struct MeshFace {
+    pub axis: usize,
+    pub positive: bool,
+    pub min: [i32; 3],
+    pub max: [i32; 3],
+    pub id: BlockId,
+}
+
+// cuánto se ahorra en cada paso: todos los bloques, los que tienen alguna cara a la vista,
+// las caras sueltas de los opacos y lo que llega al render (quads + cubos que no se fusionan)
+#[derive(Debug, Default, Clone, Copy)]
+pub struct MeshStats {
+    pub blocks: usize,
+    pub visible_blocks: usize,
+    pub block_faces: usize,
+    pub quads: usize,
+    pub cubes: usize,
+}
+
+impl MeshStats {
+    // al rehacer un chunk se resta lo que tenía y se suma lo nuevo, sin recorrer los demás
+    pub fn add(&mut self, mesh: &ChunkMesh) {
+        self.visible_blocks += mesh.visible_blocks;
+        self.block_faces += mesh.block_faces;
+        self.quads += mesh.quads.len();
+        self.cubes += mesh.cubes.len();
+    }
+
+    pub fn remove(&mut self, mesh: &ChunkMesh) {
+        self.visible_blocks -= mesh.visible_blocks;
+        self.block_faces -= mesh.block_faces;
+        self.quads -= mesh.quads.len();
+        self.cubes -= mesh.cubes.len();
+    }
+
+    pub fn print_summary(&self) {
+        println!(
+            "Geometría: {} bloques, {} a la vista -> {} primitivas ({} quads de {} caras + {} cubos)",
+            self.blocks, self.visible_blocks, self.quads + self.cubes, self.quads, self.block_faces, self.cubes,
+        );
+    }
+}
+
+// mallado greedy de los bloques opacos: por cada dirección y capa se arma una máscara con las caras
+// a la vista y se toman rectángulos del mismo bloque, primero lo más anchos y después lo más altos.
+// devuelve las caras fusionadas y cuántas caras sueltas había
+pub fn greedy_faces(world: &World, opaque: &[(i32, i32, i32, BlockId)], keep: impl Fn(i32, i32, i32) -> bool) -> (Vec<MeshFace>, usize) {
+    let mut faces = Vec::new();
+    let mut block_faces = 0;
+    if opaque.is_empty() {
+        return (faces, block_faces);
+    }
+
+    let mut lo = [i32::MAX; 3];
+    let mut hi = [i32::MIN; 3];
+    for &(x, y, z, _) in opaque {
+        for (i, c) in [x, y, z].into_iter().enumerate() {
+            lo[i] = lo[i].min(c);
+            hi[i] = hi[i].max(c);
+        }
+    }
+
+    let visible = |p: [i32; 3]| keep(p[0], p[1], p[2]) && world.block(p[0], p[1], p[2]).is_opaque();
+
+    for axis in 0..3 {
+        let (u_axis, v_axis) = ((axis + 1) % 3, (axis + 2) % 3);
+        let width = (hi[u_axis] - lo[u_axis] + 1) as usize;
+        let height = (hi[v_axis] - lo[v_axis] + 1) as usize;
+        for positive in [true, false] {
+            let step = if positive { 1 } else { -1 };
+            for layer in lo[axis]..=hi[axis] {
+                let mut mask: Vec<Option<BlockId>> = vec![None; width * height];
+                for v in 0..height {
+                    for u in 0..width {
+                        let mut p = [0; 3];
+                        p[axis] = layer;
+                        p[u_axis] = lo[u_axis] + u as i32;
+                        p[v_axis] = lo[v_axis] + v as i32;
+                        if !visible(p) {
+                            continue;
+                        }
+                        let mut neighbor = p;
+                        neighbor[axis] += step;
+                        if !visible(neighbor) {
+                            mask[u + v * width] = Some(world.get(p[0], p[1], p[2]));
+                            block_faces += 1;
+                        }
+                    }
+                }
+
+                for v in 0..height {
+                    let mut u = 0;
+                    while u < width {
+                        let Some(id) = mask[u + v * width] else {
+                            u += 1;
+                            continue;
+                        };
+                        let mut w = 1;
+                        while u + w < width && mask[u + w + v * width] == Some(id) {
+                            w += 1;
+                        }
+                        let mut h = 1;
+                        while v + h < height && (0..w).all(|k| mask[u + k + (v + h) * width] == Some(id)) {
+                            h += 1;
+                        }
+                        for dv in 0..h {
+                            for du in 0..w {
+                                mask[u + du + (v + dv) * width] = None;
+                            }
+                        }
+
+                        let mut min = [0; 3];
+                        min[axis] = layer + if positive { 1 } else { 0 };
+                        min[u_axis] = lo[u_axis] + u as i32;
+                        min[v_axis] = lo[v_axis] + v as i32;
+                        let mut max = min;
+                        max[u_axis] += w as i32;
+                        max[v_axis] += h as i32;
+                        faces.push(MeshFace { axis, positive, min, max, id });
+                        u += w;
+                    }
+                }
+            }
+        }
+    }
+    (faces, block_faces)
+}
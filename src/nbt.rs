@@ -0,0 +1,172 @@
+use std::collections::HashMap;
+use std::error::Error;
+
+// valor NBT (el formato de datos de Minecraft, big endian). los enteros van todos a i64;
+// floats, doubles y arreglos de longs se leen pero no se guardan porque el importador no los usa
+#[derive(Debug)]
+pub enum Tag {
+    Int(i64),
+    Skipped,
+    ByteArray(Vec<u8>),
+    String(String),
+    List(Vec<Tag>),
+    Compound(HashMap<String, Tag>),
+    IntArray(Vec<i32>),
+}
+
+impl Tag {
+    pub fn get(&self, name: &str) -> Option<&Tag> {
+        match self {
+            Tag::Compound(entries) => entries.get(name),
+            _ => None,
+        }
+    }
+
+    pub fn as_int(&self) -> Option<i64> {
+        match self {
+            Tag::Int(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Tag::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_list(&self) -> Option<&[Tag]> {
+        match self {
+            Tag::List(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    pub fn as_compound(&self) -> Option<&HashMap<String, Tag>> {
+        match self {
+            Tag::Compound(entries) => Some(entries),
+            _ => None,
+        }
+    }
+
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            Tag::ByteArray(bytes) => Some(bytes),
+            _ => None,
+        }
+    }
+
+    // tres enteros, como las posiciones y tamaños (lista de ints o arreglo de ints)
+    pub fn as_position(&self) -> Option<[i32; 3]> {
+        let values: Vec<i32> = match self {
+            Tag::List(items) => items.iter().map(|item| item.as_int().map(|v| v as i32)).collect::<Option<_>>()?,
+            Tag::IntArray(values) => values.clone(),
+            _ => return None,
+        };
+        values.try_into().ok()
+    }
+}
+
+// como Minecraft: más anidado que esto es un archivo roto (y evita quedarse sin pila)
+const MAX_DEPTH: usize = 512;
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], Box<dyn Error>> {
+        let slice = self.bytes.get(self.position..self.position + len).ok_or("NBT incompleto")?;
+        self.position += len;
+        Ok(slice)
+    }
+
+    fn int(&mut self, len: usize) -> Result<i64, Box<dyn Error>> {
+        let bytes = self.take(len)?;
+        // con signo: se arranca del primer byte extendido
+        let mut value = bytes[0] as i8 as i64;
+        for &b in &bytes[1..] {
+            value = (value << 8) | b as i64;
+        }
+        Ok(value)
+    }
+
+    fn length(&mut self) -> Result<usize, Box<dyn Error>> {
+        let len = self.int(4)?;
+        if len < 0 || len as usize > self.bytes.len() {
+            return Err(format!("largo inválido en NBT: {}", len).into());
+        }
+        Ok(len as usize)
+    }
+
+    fn string(&mut self) -> Result<String, Box<dyn Error>> {
+        let len = self.int(2)? as u16 as usize;
+        // Java usa un UTF-8 modificado; para nombres de bloques alcanza con el común
+        Ok(String::from_utf8_lossy(self.take(len)?).into_owned())
+    }
+
+    // `depth` cuenta cuántas listas y compounds hay por encima
+    fn payload(&mut self, kind: u8, depth: usize) -> Result<Tag, Box<dyn Error>> {
+        if matches!(kind, 9 | 10) && depth >= MAX_DEPTH {
+            return Err(format!("NBT anidado más de {} niveles", MAX_DEPTH).into());
+        }
+        Ok(match kind {
+            1 => Tag::Int(self.int(1)?),
+            2 => Tag::Int(self.int(2)?),
+            3 => Tag::Int(self.int(4)?),
+            4 => Tag::Int(self.int(8)?),
+            5 => {
+                self.take(4)?;
+                Tag::Skipped
+            }
+            6 => {
+                self.take(8)?;
+                Tag::Skipped
+            }
+            7 => {
+                let len = self.length()?;
+                Tag::ByteArray(self.take(len)?.to_vec())
+            }
+            8 => Tag::String(self.string()?),
+            9 => {
+                let item_kind = self.int(1)? as u8;
+                let len = self.length()?;
+                Tag::List((0..len).map(|_| self.payload(item_kind, depth + 1)).collect::<Result<_, _>>()?)
+            }
+            10 => {
+                let mut entries = HashMap::new();
+                loop {
+                    let kind = self.int(1)? as u8;
+                    if kind == 0 {
+                        break;
+                    }
+                    let name = self.string()?;
+                    entries.insert(name, self.payload(kind, depth + 1)?);
+                }
+                Tag::Compound(entries)
+            }
+            11 => {
+                let len = self.length()?;
+                Tag::IntArray((0..len).map(|_| self.int(4).map(|v| v as i32)).collect::<Result<_, _>>()?)
+            }
+            12 => {
+                let len = self.length()?;
+                self.take(len * 8)?;
+                Tag::Skipped
+            }
+            _ => return Err(format!("tipo de NBT desconocido: {}", kind).into()),
+        })
+    }
+}
+
+// el archivo es un compound con nombre (casi siempre vacío) en la raíz
+pub fn parse(bytes: &[u8]) -> Result<Tag, Box<dyn Error>> {
+    let mut reader = Reader { bytes, position: 0 };
+    if reader.int(1)? != 10 {
+        return Err("la raíz del NBT no es un compound".into());
+    }
+    reader.string()?;
+    reader.payload(10, 0)
+}
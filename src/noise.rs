@@ -0,0 +1,244 @@
+// ruido coherente con semilla: Perlin, Simplex, Worley, fBm y domain warping.
+// Las variantes `_tiled` se repiten cada `period` unidades, para texturas que empatan en los bordes.
+
+#[derive(Debug, Clone, Copy)]
+pub struct Noise {
+    seed: u32,
+}
+
+// quíntica de Perlin, derivada segunda continua
+fn fade(t: f32) -> f32 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+fn wrap(i: i32, period: Option<i32>) -> i32 {
+    match period {
+        Some(p) if p > 0 => i.rem_euclid(p),
+        _ => i,
+    }
+}
+
+impl Noise {
+    pub fn new(seed: u32) -> Self {
+        Noise { seed }
+    }
+
+    // otra semilla derivada, para octavas o canales independientes
+    pub fn derived(&self, salt: u32) -> Self {
+        Noise::new(self.seed.wrapping_mul(0x9E37_79B9).wrapping_add(salt.wrapping_mul(0x85EB_CA6B)) ^ salt)
+    }
+
+    fn hash(&self, x: i32, y: i32, z: i32) -> u32 {
+        let mut h = self.seed
+            ^ (x as u32).wrapping_mul(0x8DA6_B343)
+            ^ (y as u32).wrapping_mul(0xD816_3841)
+            ^ (z as u32).wrapping_mul(0xCB1A_B31F);
+        h = (h ^ (h >> 15)).wrapping_mul(0x2C1B_3C6D);
+        h = (h ^ (h >> 12)).wrapping_mul(0x297A_2D39);
+        h ^ (h >> 15)
+    }
+
+    fn hash_unit(&self, x: i32, y: i32, z: i32) -> f32 {
+        self.hash(x, y, z) as f32 / u32::MAX as f32
+    }
+
+    fn gradient2(&self, ix: i32, iy: i32, dx: f32, dy: f32) -> f32 {
+        // 8 direcciones: ejes y diagonales
+        match self.hash(ix, iy, 0) & 7 {
+            0 => dx,
+            1 => -dx,
+            2 => dy,
+            3 => -dy,
+            4 => (dx + dy) * std::f32::consts::FRAC_1_SQRT_2,
+            5 => (dx - dy) * std::f32::consts::FRAC_1_SQRT_2,
+            6 => (-dx + dy) * std::f32::consts::FRAC_1_SQRT_2,
+            _ => (-dx - dy) * std::f32::consts::FRAC_1_SQRT_2,
+        }
+    }
+
+    fn gradient3(&self, ix: i32, iy: i32, iz: i32, dx: f32, dy: f32, dz: f32) -> f32 {
+        // las 12 aristas del cubo de Perlin
+        match self.hash(ix, iy, iz) % 12 {
+            0 => dx + dy,
+            1 => -dx + dy,
+            2 => dx - dy,
+            3 => -dx - dy,
+            4 => dx + dz,
+            5 => -dx + dz,
+            6 => dx - dz,
+            7 => -dx - dz,
+            8 => dy + dz,
+            9 => -dy + dz,
+            10 => dy - dz,
+            _ => -dy - dz,
+        }
+    }
+
+    fn perlin2_impl(&self, x: f32, y: f32, period: Option<i32>) -> f32 {
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fx, fy) = (x - x0, y - y0);
+        let (ix, iy) = (x0 as i32, y0 as i32);
+        let (ix0, iy0) = (wrap(ix, period), wrap(iy, period));
+        let (ix1, iy1) = (wrap(ix + 1, period), wrap(iy + 1, period));
+
+        let n00 = self.gradient2(ix0, iy0, fx, fy);
+        let n10 = self.gradient2(ix1, iy0, fx - 1.0, fy);
+        let n01 = self.gradient2(ix0, iy1, fx, fy - 1.0);
+        let n11 = self.gradient2(ix1, iy1, fx - 1.0, fy - 1.0);
+
+        let (u, v) = (fade(fx), fade(fy));
+        // el máximo teórico es √½, se escala a [-1, 1]
+        lerp(lerp(n00, n10, u), lerp(n01, n11, u), v) * std::f32::consts::SQRT_2
+    }
+
+    // Perlin 2D en [-1, 1]
+    pub fn perlin2(&self, x: f32, y: f32) -> f32 {
+        self.perlin2_impl(x, y, None)
+    }
+
+    pub fn perlin2_tiled(&self, x: f32, y: f32, period: i32) -> f32 {
+        self.perlin2_impl(x, y, Some(period))
+    }
+
+    // Perlin 3D en [-1, 1] aprox.
+    pub fn perlin3(&self, x: f32, y: f32, z: f32) -> f32 {
+        let (x0, y0, z0) = (x.floor(), y.floor(), z.floor());
+        let (fx, fy, fz) = (x - x0, y - y0, z - z0);
+        let (ix, iy, iz) = (x0 as i32, y0 as i32, z0 as i32);
+        let (u, v, w) = (fade(fx), fade(fy), fade(fz));
+
+        let corner = |cx: i32, cy: i32, cz: i32| {
+            self.gradient3(ix + cx, iy + cy, iz + cz, fx - cx as f32, fy - cy as f32, fz - cz as f32)
+        };
+
+        let x00 = lerp(corner(0, 0, 0), corner(1, 0, 0), u);
+        let x10 = lerp(corner(0, 1, 0), corner(1, 1, 0), u);
+        let x01 = lerp(corner(0, 0, 1), corner(1, 0, 1), u);
+        let x11 = lerp(corner(0, 1, 1), corner(1, 1, 1), u);
+        lerp(lerp(x00, x10, v), lerp(x01, x11, v), w)
+    }
+
+    // Simplex 2D en [-1, 1]; menos artefactos de rejilla que Perlin, pero no se puede repetir
+    pub fn simplex2(&self, x: f32, y: f32) -> f32 {
+        const F2: f32 = 0.366_025_42;  // (√3 - 1) / 2
+        const G2: f32 = 0.211_324_87;  // (3 - √3) / 6
+
+        let s = (x + y) * F2;
+        let (i, j) = ((x + s).floor(), (y + s).floor());
+        let t = (i + j) * G2;
+        let (x0, y0) = (x - (i - t), y - (j - t));
+
+        let (i1, j1) = if x0 > y0 { (1, 0) } else { (0, 1) };
+        let (x1, y1) = (x0 - i1 as f32 + G2, y0 - j1 as f32 + G2);
+        let (x2, y2) = (x0 - 1.0 + 2.0 * G2, y0 - 1.0 + 2.0 * G2);
+        let (ii, jj) = (i as i32, j as i32);
+
+        let contribution = |gx: i32, gy: i32, dx: f32, dy: f32| {
+            let t = 0.5 - dx * dx - dy * dy;
+            if t < 0.0 { 0.0 } else { t.powi(4) * self.gradient2(gx, gy, dx, dy) }
+        };
+
+        let n = contribution(ii, jj, x0, y0)
+            + contribution(ii + i1, jj + j1, x1, y1)
+            + contribution(ii + 1, jj + 1, x2, y2);
+        (n * 99.0).clamp(-1.0, 1.0)
+    }
+
+    fn worley2_impl(&self, x: f32, y: f32, period: Option<i32>) -> (f32, f32) {
+        let (cx, cy) = (x.floor() as i32, y.floor() as i32);
+        let mut f1 = f32::INFINITY;
+        let mut f2 = f32::INFINITY;
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                let (gx, gy) = (cx + dx, cy + dy);
+                let (hx, hy) = (wrap(gx, period), wrap(gy, period));
+                // un punto por celda, en una posición al azar dentro de ella
+                let px = gx as f32 + self.hash_unit(hx, hy, 1);
+                let py = gy as f32 + self.hash_unit(hx, hy, 2);
+                let d = ((px - x).powi(2) + (py - y).powi(2)).sqrt();
+                if d < f1 {
+                    f2 = f1;
+                    f1 = d;
+                } else if d < f2 {
+                    f2 = d;
+                }
+            }
+        }
+        (f1, f2)
+    }
+
+    // Worley / celular: distancia al punto más cercano (F1) y al segundo (F2)
+    pub fn worley2(&self, x: f32, y: f32) -> (f32, f32) {
+        self.worley2_impl(x, y, None)
+    }
+
+    pub fn worley2_tiled(&self, x: f32, y: f32, period: i32) -> (f32, f32) {
+        self.worley2_impl(x, y, Some(period))
+    }
+
+    // fBm: octavas de Perlin, cada una al doble de frecuencia y la mitad de amplitud
+    pub fn fbm2(&self, x: f32, y: f32, octaves: u32) -> f32 {
+        let mut sum = 0.0;
+        let mut amplitude = 1.0;
+        let mut frequency = 1.0;
+        let mut norm = 0.0;
+        for octave in 0..octaves {
+            sum += self.derived(octave).perlin2(x * frequency, y * frequency) * amplitude;
+            norm += amplitude;
+            amplitude *= 0.5;
+            frequency *= 2.0;
+        }
+        sum / norm
+    }
+
+    pub fn fbm2_tiled(&self, x: f32, y: f32, period: i32, octaves: u32) -> f32 {
+        let mut sum = 0.0;
+        let mut amplitude = 1.0;
+        let mut frequency = 1;
+        let mut norm = 0.0;
+        for octave in 0..octaves {
+            let f = frequency as f32;
+            sum += self.derived(octave).perlin2_tiled(x * f, y * f, period * frequency) * amplitude;
+            norm += amplitude;
+            amplitude *= 0.5;
+            frequency *= 2;
+        }
+        sum / norm
+    }
+
+    pub fn fbm3(&self, x: f32, y: f32, z: f32, octaves: u32) -> f32 {
+        let mut sum = 0.0;
+        let mut amplitude = 1.0;
+        let mut frequency = 1.0;
+        let mut norm = 0.0;
+        for octave in 0..octaves {
+            sum += self.derived(octave).perlin3(x * frequency, y * frequency, z * frequency) * amplitude;
+            norm += amplitude;
+            amplitude *= 0.5;
+            frequency *= 2.0;
+        }
+        sum / norm
+    }
+
+    // domain warping: el fBm se evalúa en un punto desplazado por otro fBm
+    pub fn domain_warp2(&self, x: f32, y: f32, strength: f32, octaves: u32) -> f32 {
+        let wx = self.derived(101).fbm2(x + 5.2, y + 1.3, octaves);
+        let wy = self.derived(202).fbm2(x + 1.7, y + 9.2, octaves);
+        self.fbm2(x + strength * wx, y + strength * wy, octaves)
+    }
+
+    pub fn domain_warp2_tiled(&self, x: f32, y: f32, period: i32, strength: f32, octaves: u32) -> f32 {
+        let wx = self.derived(101).fbm2_tiled(x, y, period, octaves);
+        let wy = self.derived(202).fbm2_tiled(x, y, period, octaves);
+        self.fbm2_tiled(x + strength * wx, y + strength * wy, period, octaves)
+    }
+}
+
+// pasa un valor de [-1, 1] a [0, 1]
+pub fn to_unit(value: f32) -> f32 {
+    (value * 0.5 + 0.5).clamp(0.0, 1.0)
+}
@@ -0,0 +1,177 @@
+use crate::block::BlockRegistry;
+use crate::color::Color;
+use crate::material::Material;
+use crate::triangle::{Triangle, TriangleMesh};
+use nalgebra_glm::{Vec3, cross, normalize};
+use std::collections::HashMap;
+use std::error::Error;
+use std::path::Path;
+
+fn numbers<const N: usize>(fields: &[&str], number: usize) -> Result<[f32; N], Box<dyn Error>> {
+    let mut values = [0.0; N];
+    for (i, value) in values.iter_mut().enumerate() {
+        *value = fields.get(i).ok_or(format!("línea {}: faltan valores", number))?
+            .parse().map_err(|_| format!("línea {}: número inválido", number))?;
+    }
+    Ok(values)
+}
+
+// material de un .mtl: color difuso (Kd), brillo (Ns, Ks), opacidad (d o Tr) e IOR (Ni).
+// lo que no es opaco pasa a vidrio de ese color
+fn read_mtl(path: &Path) -> Result<HashMap<String, Material>, Box<dyn Error>> {
+    let mut materials = HashMap::new();
+    let mut current: Option<(String, Material, f32)> = None;
+    let mut finish = |current: Option<(String, Material, f32)>| {
+        if let Some((name, mut material, opacity)) = current {
+            if opacity < 1.0 {
+                let ior = material.refractive_index;
+                material = Material::tinted_glass(material.diffuse);
+                material.refractive_index = ior;
+            }
+            materials.insert(name, material);
+        }
+    };
+    for (number, line) in std::fs::read_to_string(path)?.lines().enumerate() {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let Some((&keyword, values)) = fields.split_first() else { continue };
+        if keyword == "newmtl" {
+            finish(current.take());
+            current = Some((values.join(" "), Material::new(Color::new(200, 200, 200)), 1.0));
+            continue;
+        }
+        let Some((_, material, opacity)) = &mut current else { continue };
+        let to_u8 = |v: f32| (v.clamp(0.0, 1.0) * 255.0).round() as u8;
+        match keyword {
+            "Kd" => {
+                let [r, g, b] = numbers(values, number + 1)?;
+                material.diffuse = Color::new(to_u8(r), to_u8(g), to_u8(b));
+            }
+            "Ks" => {
+                let [r, g, b] = numbers(values, number + 1)?;
+                material.specular_strength = (r + g + b) / 3.0;
+            }
+            "Ns" => {
+                let [ns] = numbers(values, number + 1)?;
+                material.specular = ns;
+                // la misma relación que usa Blinn-Phong entre exponente y rugosidad
+                material.roughness = (2.0 / (ns + 2.0)).sqrt().clamp(0.05, 1.0);
+            }
+            "Ni" => material.refractive_index = numbers::<1>(values, number + 1)?[0],
+            "d" => *opacity = numbers::<1>(values, number + 1)?[0],
+            "Tr" => *opacity = 1.0 - numbers::<1>(values, number + 1)?[0],
+            _ => {}
+        }
+    }
+    finish(current);
+    Ok(materials)
+}
+
+// vértice de una cara: posición, uv y normal; las normales que faltan se calculan al final
+type Corner = (usize, Option<usize>, Option<usize>);
+
+// índice de OBJ (desde 1, negativo = desde el final) a posición en la lista
+fn index(field: &str, len: usize, number: usize) -> Result<usize, Box<dyn Error>> {
+    let value: i64 = field.parse().map_err(|_| format!("línea {}: índice inválido", number))?;
+    let resolved = if value < 0 { len as i64 + value } else { value - 1 };
+    if resolved < 0 || resolved >= len as i64 {
+        return Err(format!("línea {}: índice fuera de rango", number).into());
+    }
+    Ok(resolved as usize)
+}
+
+// lee un .obj (v, vt, vn, f y usemtl; los polígonos se abren en abanico) con los materiales de su mtllib.
+// un usemtl con el nombre de un bloque ("oak_planks", "glass") usa el material de ese bloque, con su textura;
+// las caras sin vn toman la normal promedio de las caras que comparten el vértice
+pub fn load(path: &str, registry: &mut BlockRegistry) -> Result<TriangleMesh, Box<dyn Error>> {
+    let text = std::fs::read_to_string(path)?;
+    let directory = Path::new(path).parent().unwrap_or(Path::new(""));
+    let mut positions: Vec<Vec3> = Vec::new();
+    let mut uvs: Vec<(f32, f32)> = Vec::new();
+    let mut normals: Vec<Vec3> = Vec::new();
+    let mut library: HashMap<String, Material> = HashMap::new();
+    let default_material = Material::new(Color::new(200, 200, 200));
+    let mut material = default_material;
+    let mut faces: Vec<([Corner; 3], Material)> = Vec::new();
+
+    for (number, line) in text.lines().enumerate() {
+        let number = number + 1;
+        let fields: Vec<&str> = line.split('#').next().unwrap_or("").split_whitespace().collect();
+        let Some((&keyword, values)) = fields.split_first() else { continue };
+        match keyword {
+            "v" => {
+                let [x, y, z] = numbers(values, number)?;
+                positions.push(Vec3::new(x, y, z));
+            }
+            // en OBJ la v crece hacia arriba de la imagen, en las texturas de acá hacia abajo
+            "vt" => {
+                let [u, v] = numbers(values, number)?;
+                uvs.push((u, 1.0 - v));
+            }
+            "vn" => {
+                let [x, y, z] = numbers(values, number)?;
+                normals.push(normalize(&Vec3::new(x, y, z)));
+            }
+            "f" => {
+                let corners = values.iter().map(|corner| {
+                    let mut parts = corner.split('/');
+                    let position = index(parts.next().unwrap_or(""), positions.len(), number)?;
+                    let uv = parts.next().filter(|s| !s.is_empty()).map(|s| index(s, uvs.len(), number)).transpose()?;
+                    let normal = parts.next().filter(|s| !s.is_empty()).map(|s| index(s, normals.len(), number)).transpose()?;
+                    Ok((position, uv, normal))
+                }).collect::<Result<Vec<_>, Box<dyn Error>>>()?;
+                if corners.len() < 3 {
+                    return Err(format!("línea {}: cara con menos de 3 vértices", number).into());
+                }
+                for i in 1..corners.len() - 1 {
+                    faces.push(([corners[0], corners[i], corners[i + 1]], material));
+                }
+            }
+            "mtllib" => {
+                let file = directory.join(values.join(" "));
+                match read_mtl(&file) {
+                    Ok(materials) => library.extend(materials),
+                    Err(err) => println!("No se pudo leer {}: {}", file.display(), err),
+                }
+            }
+            "usemtl" => {
+                let name = values.join(" ");
+                material = match registry.resolve(&name) {
+                    Some(id) if !id.is_air() => registry.get(id).material,
+                    _ => library.get(&name).copied().unwrap_or(default_material),
+                };
+            }
+            _ => {}
+        }
+    }
+    if faces.is_empty() {
+        return Err("el .obj no tiene caras".into());
+    }
+
+    // normal de cada posición sumando las de sus caras (las más grandes pesan más)
+    let mut smooth = vec![Vec3::zeros(); positions.len()];
+    for (corners, _) in &faces {
+        let [a, b, c] = corners.map(|(p, _, _)| positions[p]);
+        let face_normal = cross(&(b - a), &(c - a));
+        for (p, _, _) in corners {
+            smooth[*p] += face_normal;
+        }
+    }
+
+    let triangles = faces.into_iter().filter_map(|(corners, material)| {
+        let vertices = corners.map(|(p, _, _)| positions[p]);
+        let face_normal = cross(&(vertices[1] - vertices[0]), &(vertices[2] - vertices[0]));
+        // las caras degeneradas no se ven y ensucian las normales
+        if face_normal.norm() < 1e-12 {
+            return None;
+        }
+        let normals = corners.map(|(p, _, n)| match n {
+            Some(n) => normals[n],
+            None if smooth[p].norm() > 1e-12 => normalize(&smooth[p]),
+            None => normalize(&face_normal),
+        });
+        let default_uvs = [(0.0, 1.0), (1.0, 1.0), (0.0, 0.0)];
+        let uvs = [0, 1, 2].map(|i| corners[i].1.map_or(default_uvs[i], |t| uvs[t]));
+        Some(Triangle { vertices, normals, uvs, material })
+    }).collect();
+    Ok(TriangleMesh::new(triangles))
+}
@@ -0,0 +1,104 @@
+use crate::rng::Rng;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VeinShape {
+    Vein,  // tira que serpentea, de uno o dos bloques de grueso
+    Blob,  // bolita compacta
+}
+
+// una regla de menas: qué bloque (nombre en el registro), entre qué alturas, cuántas vetas y de qué tamaño
+#[derive(Debug, Clone, Copy)]
+pub struct OreVein {
+    pub block: &'static str,
+    pub min_y: usize,
+    pub max_y: usize,
+    pub frequency: f32,  // vetas por cada 16x16 columnas
+    pub size: usize,     // bloques aproximados por veta
+    pub shape: VeinShape,
+}
+
+impl OreVein {
+    // como en Minecraft: carbón por todos lados, el diamante solo al fondo
+    pub fn defaults() -> Vec<OreVein> {
+        vec![
+            OreVein { block: "coal_ore", min_y: 1, max_y: 8, frequency: 6.0, size: 7, shape: VeinShape::Vein },
+            OreVein { block: "iron_ore", min_y: 1, max_y: 5, frequency: 4.0, size: 5, shape: VeinShape::Vein },
+            OreVein { block: "redstone_ore", min_y: 1, max_y: 3, frequency: 3.0, size: 5, shape: VeinShape::Blob },
+            OreVein { block: "gold_ore", min_y: 1, max_y: 3, frequency: 2.0, size: 4, shape: VeinShape::Blob },
+            OreVein { block: "diamond_ore", min_y: 1, max_y: 2, frequency: 1.0, size: 3, shape: VeinShape::Blob },
+        ]
+    }
+
+    // celdas (x, y, z) de cada veta, sin revisar si ahí hay piedra; eso lo hace el terreno
+    pub fn generate(&self, size: usize, max_height: usize, rng: &mut Rng) -> Vec<(i32, i32, i32)> {
+        let max_y = self.max_y.min(max_height.saturating_sub(1));
+        if self.min_y > max_y {
+            return Vec::new();
+        }
+
+        // la frecuencia es por 16x16 columnas; lo que sobra se sortea
+        let expected = self.frequency * (size * size) as f32 / 256.0;
+        let mut count = expected.floor() as usize;
+        if rng.next_f32() < expected.fract() {
+            count += 1;
+        }
+
+        let mut cells = Vec::new();
+        for _ in 0..count {
+            let start = [
+                rng.next_f32() * size as f32,
+                self.min_y as f32 + rng.next_f32() * (max_y - self.min_y + 1) as f32,
+                rng.next_f32() * size as f32,
+            ];
+            match self.shape {
+                VeinShape::Vein => self.walk(start, rng, &mut cells),
+                VeinShape::Blob => self.blob(start, rng, &mut cells),
+            }
+        }
+        cells.retain(|&(_, y, _)| y >= self.min_y as i32 && y <= max_y as i32);
+        cells
+    }
+
+    fn walk(&self, start: [f32; 3], rng: &mut Rng, cells: &mut Vec<(i32, i32, i32)>) {
+        let angle = rng.next_f32() * 2.0 * std::f32::consts::PI;
+        let mut direction = [angle.cos(), (rng.next_f32() - 0.5) * 0.6, angle.sin()];
+        let mut p = start;
+        for _ in 0..self.size {
+            cells.push((p[0].floor() as i32, p[1].floor() as i32, p[2].floor() as i32));
+            // a veces engorda con un bloque de al lado
+            if rng.next_f32() < 0.4 {
+                let axis = ((rng.next_f32() * 3.0) as usize).min(2);
+                let mut side = (p[0].floor() as i32, p[1].floor() as i32, p[2].floor() as i32);
+                match axis {
+                    0 => side.0 += 1,
+                    1 => side.1 += 1,
+                    _ => side.2 += 1,
+                }
+                cells.push(side);
+            }
+            for (d, v) in direction.iter_mut().zip(p.iter_mut()) {
+                *d += (rng.next_f32() - 0.5) * 0.5;
+                *v += *d;
+            }
+            let len = (direction[0] * direction[0] + direction[1] * direction[1] + direction[2] * direction[2]).sqrt().max(1e-3);
+            direction = [direction[0] / len, direction[1] / len * 0.6, direction[2] / len];
+        }
+    }
+
+    fn blob(&self, start: [f32; 3], rng: &mut Rng, cells: &mut Vec<(i32, i32, i32)>) {
+        // radio de una esfera con `size` bloques de volumen, y el borde irregular
+        let radius = (self.size as f32 * 3.0 / (4.0 * std::f32::consts::PI)).cbrt().max(0.6);
+        let r = radius.ceil() as i32 + 1;
+        let center = (start[0].floor() as i32, start[1].floor() as i32, start[2].floor() as i32);
+        for dy in -r..=r {
+            for dz in -r..=r {
+                for dx in -r..=r {
+                    let d = ((dx * dx + dy * dy + dz * dz) as f32).sqrt();
+                    if d <= radius * (0.8 + 0.5 * rng.next_f32()) {
+                        cells.push((center.0 + dx, center.1 + dy, center.2 + dz));
+                    }
+                }
+            }
+        }
+    }
+}
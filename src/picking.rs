@@ -0,0 +1,30 @@
+use nalgebra_glm::{Vec3, dot};
+
+// la cara del bloque que apunta el mouse, en coordenadas del mundo
+#[derive(Debug, Clone, Copy)]
+pub struct Highlight {
+    pub min: Vec3,
+    pub max: Vec3,
+    pub normal: Vec3,
+}
+
+impl Highlight {
+    // el punto cae sobre esa cara (o sobre el bloque chico que está en esa celda, mirando igual)
+    pub fn contains(&self, point: &Vec3, normal: &Vec3) -> bool {
+        let eps = 1e-3;
+        dot(normal, &self.normal) > 0.9
+            && (0..3).all(|i| point[i] >= self.min[i] - eps && point[i] <= self.max[i] + eps)
+    }
+}
+
+// el punto está sobre una arista de la caja: adentro y pegado a dos de sus planos
+pub fn on_box_edge(min: &Vec3, max: &Vec3, point: &Vec3, eps: f32) -> bool {
+    let inside = (0..3).all(|i| point[i] >= min[i] - eps && point[i] <= max[i] + eps);
+    let planes = (0..3).filter(|&i| (point[i] - min[i]).abs() < eps || (point[i] - max[i]).abs() < eps).count();
+    inside && planes >= 2
+}
+
+// bloques que se pueden poner, con las teclas 1 a 9
+pub const HOTBAR: [&str; 9] = [
+    "stone", "dirt", "grass_block", "oak_planks", "oak_log", "glass", "sand", "obsidian", "crystal",
+];
@@ -0,0 +1,54 @@
+use nalgebra_glm::Vec3;
+use crate::material::Material;
+
+// cara rectangular alineada a los ejes, que puede cubrir varios bloques del mismo material;
+// la textura se repite una vez por bloque, con el mismo mapeo que las caras de Cube
+#[derive(Clone)]
+pub struct Quad {
+    pub axis: usize,   // eje de la normal: min[axis] == max[axis]
+    pub normal: Vec3,
+    pub min: Vec3,
+    pub max: Vec3,
+    pub tile_size: f32,
+    pub material: Material,
+}
+
+impl Quad {
+    pub fn new(axis: usize, positive: bool, min: Vec3, max: Vec3, tile_size: f32, material: Material) -> Self {
+        let mut normal = Vec3::new(0.0, 0.0, 0.0);
+        normal[axis] = if positive { 1.0 } else { -1.0 };
+        Quad { axis, normal, min, max, tile_size, material }
+    }
+
+    // solo de frente: las caras miran hacia afuera de bloques opacos
+    pub fn ray_intersect(&self, ray_origin: &Vec3, ray_direction: &Vec3) -> Option<f32> {
+        let denom = ray_direction[self.axis] * self.normal[self.axis];
+        if denom > -1e-6 {
+            return None;
+        }
+        let t = (self.min[self.axis] - ray_origin[self.axis]) / ray_direction[self.axis];
+        if t <= 0.001 {
+            return None;
+        }
+        let p = ray_origin + ray_direction * t;
+        let inside = (0..3).filter(|&i| i != self.axis).all(|i| p[i] >= self.min[i] && p[i] <= self.max[i]);
+        if inside { Some(t) } else { None }
+    }
+
+    pub fn get_uv_coordinates(&self, point: &Vec3) -> (f32, f32) {
+        let local = (point - self.min) / self.tile_size;
+        match self.axis {
+            0 => (local.z.fract(), 1.0 - local.y.fract()),
+            1 => (local.x.fract(), local.z.fract()),
+            _ => (local.x.fract(), 1.0 - local.y.fract()),
+        }
+    }
+
+    pub fn get_tangent_frame(&self) -> (Vec3, Vec3) {
+        match self.axis {
+            0 => (Vec3::new(0.0, 0.0, 1.0), Vec3::new(0.0, -1.0, 0.0)),
+            1 => (Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 1.0)),
+            _ => (Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, -1.0, 0.0)),
+        }
+    }
+}
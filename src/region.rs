@@ -0,0 +1,141 @@
+use crate::block::BlockId;
+use crate::world::World;
+
+// cambios que arman las herramientas; se aplican juntos con `OptimizedDiorama::edit`
+pub type Placement = ([i32; 3], BlockId);
+
+// caja de bloques entre dos esquinas elegidas, con las dos incluidas
+#[derive(Debug, Clone, Copy)]
+pub struct Region {
+    pub min: [i32; 3],
+    pub max: [i32; 3],
+}
+
+impl Region {
+    pub fn from_corners(a: [i32; 3], b: [i32; 3]) -> Self {
+        Region {
+            min: [a[0].min(b[0]), a[1].min(b[1]), a[2].min(b[2])],
+            max: [a[0].max(b[0]), a[1].max(b[1]), a[2].max(b[2])],
+        }
+    }
+
+    pub fn size(&self) -> [i32; 3] {
+        [self.max[0] - self.min[0] + 1, self.max[1] - self.min[1] + 1, self.max[2] - self.min[2] + 1]
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = [i32; 3]> + '_ {
+        (self.min[1]..=self.max[1]).flat_map(move |y| {
+            (self.min[2]..=self.max[2]).flat_map(move |z| (self.min[0]..=self.max[0]).map(move |x| [x, y, z]))
+        })
+    }
+
+    fn on_border(&self, p: [i32; 3], axes: &[usize]) -> bool {
+        axes.iter().any(|&i| p[i] == self.min[i] || p[i] == self.max[i])
+    }
+}
+
+pub fn fill(region: &Region, id: BlockId) -> Vec<Placement> {
+    region.positions().map(|p| (p, id)).collect()
+}
+
+// cambia un tipo de bloque por otro dentro de la región
+pub fn replace(world: &World, region: &Region, from: BlockId, to: BlockId) -> Vec<Placement> {
+    region.positions().filter(|&[x, y, z]| world.get(x, y, z) == from).map(|p| (p, to)).collect()
+}
+
+// cáscara de la región con el bloque y el interior vacío
+pub fn hollow(region: &Region, id: BlockId) -> Vec<Placement> {
+    region.positions()
+        .map(|p| (p, if region.on_border(p, &[0, 1, 2]) { id } else { BlockId::AIR }))
+        .collect()
+}
+
+// solo los cuatro lados, sin piso ni techo; lo de adentro no se toca
+pub fn walls(region: &Region, id: BlockId) -> Vec<Placement> {
+    region.positions().filter(|&p| region.on_border(p, &[0, 2])).map(|p| (p, id)).collect()
+}
+
+// bloques copiados de una región, relativos a su esquina mínima
+#[derive(Debug, Clone)]
+pub struct Clipboard {
+    size: [i32; 3],
+    blocks: Vec<BlockId>,
+}
+
+impl Clipboard {
+    pub fn copy(world: &World, region: &Region) -> Self {
+        let blocks = region.positions().map(|[x, y, z]| world.get(x, y, z)).collect();
+        Clipboard { size: region.size(), blocks }
+    }
+
+    pub fn size(&self) -> [i32; 3] {
+        self.size
+    }
+
+    fn index(&self, [x, y, z]: [i32; 3]) -> usize {
+        ((y * self.size[2] + z) * self.size[0] + x) as usize
+    }
+
+    // arma un clipboard nuevo del tamaño dado, tomando cada bloque de `source(posición nueva)`
+    fn remap(&self, size: [i32; 3], source: impl Fn([i32; 3]) -> [i32; 3]) -> Self {
+        let region = Region { min: [0, 0, 0], max: [size[0] - 1, size[1] - 1, size[2] - 1] };
+        let blocks = region.positions().map(|p| self.blocks[self.index(source(p))]).collect();
+        Clipboard { size, blocks }
+    }
+
+    // 90° alrededor del eje y, visto desde arriba en sentido horario
+    pub fn rotate(&self) -> Self {
+        let [sx, sy, sz] = self.size;
+        self.remap([sz, sy, sx], |[x, y, z]| [z, y, sz - 1 - x])
+    }
+
+    // espejo sobre el eje dado (0 = x, 1 = y, 2 = z)
+    pub fn mirror(&self, axis: usize) -> Self {
+        self.remap(self.size, |mut p| {
+            p[axis] = self.size[axis] - 1 - p[axis];
+            p
+        })
+    }
+
+    // pega con la esquina mínima en `origin`; el aire copiado no borra lo que ya hay
+    pub fn paste(&self, origin: [i32; 3]) -> Vec<Placement> {
+        let region = Region { min: [0, 0, 0], max: [self.size[0] - 1, self.size[1] - 1, self.size[2] - 1] };
+        region.positions()
+            .map(|p| (p, self.blocks[self.index(p)]))
+            .filter(|(_, id)| !id.is_air())
+            .map(|([x, y, z], id)| ([origin[0] + x, origin[1] + y, origin[2] + z], id))
+            .collect()
+    }
+}
+
+// lo generado por una forma: maciza, o solo los bloques que tocan el afuera
+fn shape(region: Region, inside: impl Fn([i32; 3]) -> bool, id: BlockId, hollow: bool) -> Vec<Placement> {
+    let neighbors = [(1, 0, 0), (-1, 0, 0), (0, 1, 0), (0, -1, 0), (0, 0, 1), (0, 0, -1)];
+    region.positions()
+        .filter(|&p| inside(p))
+        .filter(|&[x, y, z]| !hollow || neighbors.iter().any(|&(dx, dy, dz)| !inside([x + dx, y + dy, z + dz])))
+        .map(|p| (p, id))
+        .collect()
+}
+
+pub fn sphere(center: [i32; 3], radius: i32, id: BlockId, hollow: bool) -> Vec<Placement> {
+    let r = radius as f32 + 0.5;
+    let region = Region::from_corners(center.map(|c| c - radius), center.map(|c| c + radius));
+    let inside = |p: [i32; 3]| {
+        let d = [p[0] - center[0], p[1] - center[1], p[2] - center[2]].map(|c| c as f32);
+        d[0] * d[0] + d[1] * d[1] + d[2] * d[2] <= r * r
+    };
+    shape(region, inside, id, hollow)
+}
+
+// cilindro vertical parado sobre `base`
+pub fn cylinder(base: [i32; 3], radius: i32, height: i32, id: BlockId, hollow: bool) -> Vec<Placement> {
+    let r = radius as f32 + 0.5;
+    let top = base[1] + height - 1;
+    let region = Region::from_corners([base[0] - radius, base[1], base[2] - radius], [base[0] + radius, top, base[2] + radius]);
+    let inside = |p: [i32; 3]| {
+        let (dx, dz) = ((p[0] - base[0]) as f32, (p[2] - base[2]) as f32);
+        p[1] >= base[1] && p[1] <= top && dx * dx + dz * dz <= r * r
+    };
+    shape(region, inside, id, hollow)
+}
@@ -0,0 +1,28 @@
+use nalgebra_glm::Vec3;
+
+// generador pequeño (xorshift) sin dependencias; lo usan el render (reflexiones glossy) y la generación del mundo
+pub struct Rng {
+    state: u32,
+}
+
+impl Rng {
+    pub fn new(seed: u32) -> Self {
+        Rng { state: seed.max(1) }
+    }
+
+    // semilla estable a partir del punto, así el ruido no parpadea entre frames
+    pub fn from_point(point: &Vec3, salt: u32) -> Self {
+        let mut h = salt.wrapping_mul(0x9E37_79B9);
+        for i in 0..3 {
+            h ^= point[i].to_bits().wrapping_add(0x7F4A_7C15).wrapping_add(h << 6).wrapping_add(h >> 2);
+        }
+        Self::new(h)
+    }
+
+    pub fn next_f32(&mut self) -> f32 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 17;
+        self.state ^= self.state << 5;
+        (self.state >> 8) as f32 / (1u32 << 24) as f32
+    }
+}
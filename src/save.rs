@@ -0,0 +1,231 @@
+use crate::{Light, OptimizedDiorama};
+use crate::block::BlockId;
+use crate::camera::OrbitCamera;
+use crate::color::Color;
+use crate::fluid::{FluidSurface, Wave};
+use crate::material::Material;
+use crate::world::{CHUNK_VOLUME, Chunk, World};
+use nalgebra_glm::Vec3;
+use std::collections::BTreeMap;
+use std::error::Error;
+
+// archivo de mundo: "DIOR", versión, semilla, cámara, luces, ubicación de los bloques (centro, tamaño,
+// desplazamiento), superficies de agua y de lava, paleta de nombres de bloque y los chunks con sus
+// bloques en tiradas (cantidad, índice en la paleta). todo little endian.
+// la versión 1 no traía los fluidos y dependía de regenerar el terreno, por eso ya no se lee
+const MAGIC: &[u8; 4] = b"DIOR";
+const VERSION: u16 = 2;
+// la versión más vieja que todavía se sabe leer
+const MIN_VERSION: u16 = 2;
+
+pub const DEFAULT_PATH: &str = "diorama.dior";
+
+type ChunkMap = BTreeMap<(i32, i32, i32), Chunk>;
+
+// lo que trae un archivo; los nombres de la paleta se resuelven con el registro del mundo al cargar
+pub struct SavedWorld {
+    pub seed: u32,
+    pub camera: OrbitCamera,
+    pub lights: Vec<Light>,
+    pub layout: (Vec3, f32, f32),
+    pub water_surfaces: Vec<FluidSurface>,
+    pub lava_surfaces: Vec<FluidSurface>,
+    palette: Vec<String>,
+    chunks: Vec<((i32, i32, i32), Vec<u16>)>,
+}
+
+impl SavedWorld {
+    // arma los chunks con los ids del registro; un nombre que el registro no conoce (ni es de color) es un error
+    pub fn chunks(&self, world: &mut World) -> Result<ChunkMap, Box<dyn Error>> {
+        let ids = self.palette.iter()
+            .map(|name| world.registry.resolve(name).ok_or_else(|| format!("bloque desconocido: {}", name)))
+            .collect::<Result<Vec<BlockId>, _>>()?;
+        let mut chunks = BTreeMap::new();
+        for (key, entries) in &self.chunks {
+            let mut chunk = Chunk::filled(BlockId::AIR);
+            for (index, &entry) in entries.iter().enumerate() {
+                let id = *ids.get(entry as usize).ok_or("índice de paleta fuera de rango")?;
+                if !id.is_air() {
+                    chunk.set(index, id);
+                }
+            }
+            chunks.insert(*key, chunk);
+        }
+        Ok(chunks)
+    }
+}
+
+pub fn save(path: &str, diorama: &OptimizedDiorama, seed: u32, camera: &OrbitCamera, lights: &[Light]) -> Result<(), Box<dyn Error>> {
+    let world = &diorama.world;
+    let mut out = Vec::new();
+    out.extend_from_slice(MAGIC);
+    out.extend_from_slice(&VERSION.to_le_bytes());
+    out.extend_from_slice(&seed.to_le_bytes());
+
+    for v in [camera.target.x, camera.target.y, camera.target.z, camera.distance, camera.yaw, camera.pitch] {
+        out.extend_from_slice(&v.to_le_bytes());
+    }
+    out.extend_from_slice(&(lights.len() as u16).to_le_bytes());
+    for light in lights {
+        for v in [light.position.x, light.position.y, light.position.z, light.intensity] {
+            out.extend_from_slice(&v.to_le_bytes());
+        }
+        out.extend_from_slice(&[light.color.r, light.color.g, light.color.b]);
+    }
+
+    let (center, cube_size, offset) = diorama.layout();
+    for v in [center.x, center.y, center.z, cube_size, offset] {
+        out.extend_from_slice(&v.to_le_bytes());
+    }
+    // el material y las ondas salen de si es agua o lava
+    for surfaces in [&diorama.water_surfaces, &diorama.lava_surfaces] {
+        out.extend_from_slice(&(surfaces.len() as u32).to_le_bytes());
+        for s in surfaces.iter() {
+            for v in [s.min_x, s.min_z, s.max_x, s.max_z, s.height, s.bottom, s.tile_size] {
+                out.extend_from_slice(&v.to_le_bytes());
+            }
+        }
+    }
+
+    // solo entran a la paleta los bloques que aparecen, el aire siempre es el 0
+    let mut palette: Vec<BlockId> = vec![BlockId::AIR];
+    let keys: Vec<_> = world.chunk_keys().into_iter()
+        .filter(|&key| world.chunk(key).is_some_and(|chunk| !chunk.is_empty()))
+        .collect();
+    let mut chunk_data = Vec::new();
+    for &key in &keys {
+        let chunk = world.chunk(key).unwrap();
+        let mut runs: Vec<(u16, u16)> = Vec::new();
+        for index in 0..CHUNK_VOLUME {
+            let id = chunk.get(index);
+            let entry = match palette.iter().position(|&p| p == id) {
+                Some(entry) => entry,
+                None => {
+                    palette.push(id);
+                    palette.len() - 1
+                }
+            } as u16;
+            match runs.last_mut() {
+                Some((count, last)) if *last == entry => *count += 1,
+                _ => runs.push((1, entry)),
+            }
+        }
+        for v in [key.0, key.1, key.2] {
+            chunk_data.extend_from_slice(&v.to_le_bytes());
+        }
+        chunk_data.extend_from_slice(&(runs.len() as u32).to_le_bytes());
+        for (count, entry) in runs {
+            chunk_data.extend_from_slice(&count.to_le_bytes());
+            chunk_data.extend_from_slice(&entry.to_le_bytes());
+        }
+    }
+
+    out.extend_from_slice(&(palette.len() as u16).to_le_bytes());
+    for id in &palette {
+        let name = &world.registry.get(*id).name;
+        out.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        out.extend_from_slice(name.as_bytes());
+    }
+    out.extend_from_slice(&(keys.len() as u32).to_le_bytes());
+    out.extend_from_slice(&chunk_data);
+
+    std::fs::write(path, out)?;
+    Ok(())
+}
+
+// lee de a poco los bytes del archivo; quedarse corto es un error
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take<const N: usize>(&mut self) -> Result<[u8; N], Box<dyn Error>> {
+        let slice = self.bytes.get(self.position..self.position + N).ok_or("archivo incompleto")?;
+        self.position += N;
+        Ok(slice.try_into().unwrap())
+    }
+
+    fn u16(&mut self) -> Result<u16, Box<dyn Error>> {
+        Ok(u16::from_le_bytes(self.take()?))
+    }
+
+    fn u32(&mut self) -> Result<u32, Box<dyn Error>> {
+        Ok(u32::from_le_bytes(self.take()?))
+    }
+
+    fn i32(&mut self) -> Result<i32, Box<dyn Error>> {
+        Ok(i32::from_le_bytes(self.take()?))
+    }
+
+    fn f32(&mut self) -> Result<f32, Box<dyn Error>> {
+        Ok(f32::from_le_bytes(self.take()?))
+    }
+
+    fn string(&mut self) -> Result<String, Box<dyn Error>> {
+        let len = self.u16()? as usize;
+        let slice = self.bytes.get(self.position..self.position + len).ok_or("archivo incompleto")?;
+        self.position += len;
+        Ok(String::from_utf8(slice.to_vec())?)
+    }
+}
+
+pub fn load(path: &str) -> Result<SavedWorld, Box<dyn Error>> {
+    let bytes = std::fs::read(path)?;
+    let mut reader = Reader { bytes: &bytes, position: 0 };
+    if &reader.take::<4>()? != MAGIC {
+        return Err("no es un archivo de diorama".into());
+    }
+    let version = reader.u16()?;
+    if version > VERSION {
+        return Err(format!("el archivo es de una versión más nueva ({}, se lee hasta la {})", version, VERSION).into());
+    }
+    if version < MIN_VERSION {
+        return Err(format!("la versión {} del archivo ya no se puede leer", version).into());
+    }
+
+    let seed = reader.u32()?;
+    let target = Vec3::new(reader.f32()?, reader.f32()?, reader.f32()?);
+    let (distance, yaw, pitch) = (reader.f32()?, reader.f32()?, reader.f32()?);
+    let mut camera = OrbitCamera::new(target, distance);
+    camera.orbit(yaw, pitch);
+
+    let mut lights = Vec::new();
+    for _ in 0..reader.u16()? {
+        let position = Vec3::new(reader.f32()?, reader.f32()?, reader.f32()?);
+        let intensity = reader.f32()?;
+        let [r, g, b] = reader.take()?;
+        lights.push(Light::new(position, Color::new(r, g, b), intensity));
+    }
+
+    let center = Vec3::new(reader.f32()?, reader.f32()?, reader.f32()?);
+    let layout = (center, reader.f32()?, reader.f32()?);
+    let mut surfaces = |material: fn() -> Material, waves: fn() -> Vec<Wave>| -> Result<Vec<FluidSurface>, Box<dyn Error>> {
+        (0..reader.u32()?).map(|_| {
+            let (min_x, min_z, max_x, max_z) = (reader.f32()?, reader.f32()?, reader.f32()?, reader.f32()?);
+            let (height, bottom, tile_size) = (reader.f32()?, reader.f32()?, reader.f32()?);
+            Ok(FluidSurface::new((min_x, min_z), (max_x, max_z), height, bottom, tile_size, material(), waves()))
+        }).collect()
+    };
+    let water_surfaces = surfaces(Material::water_surface, Wave::water_waves)?;
+    let lava_surfaces = surfaces(Material::lava_surface, Wave::lava_waves)?;
+
+    let palette = (0..reader.u16()?).map(|_| reader.string()).collect::<Result<Vec<_>, _>>()?;
+    let mut chunks = Vec::new();
+    for _ in 0..reader.u32()? {
+        let key = (reader.i32()?, reader.i32()?, reader.i32()?);
+        let mut entries = Vec::with_capacity(CHUNK_VOLUME);
+        for _ in 0..reader.u32()? {
+            let (count, entry) = (reader.u16()?, reader.u16()?);
+            entries.extend(std::iter::repeat_n(entry, count as usize));
+            if entries.len() > CHUNK_VOLUME {
+                break;
+            }
+        }
+        if entries.len() != CHUNK_VOLUME {
+            return Err(format!("chunk {:?} con {} bloques", key, entries.len()).into());
+        }
+        chunks.push((key, entries));
+    }
+    Ok(SavedWorld { seed, camera, lights, layout, water_surfaces, lava_surfaces, palette, chunks })
+}
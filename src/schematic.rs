@@ -0,0 +1,197 @@
+use crate::block::{BlockId, BlockRegistry};
+use crate::inflate;
+use crate::nbt::{self, Tag};
+use crate::region::Placement;
+use std::error::Error;
+
+// construcción de Minecraft: tamaño y bloques con su id sin namespace ni propiedades ("oak_stairs").
+// el aire no se guarda
+pub struct Structure {
+    pub size: [i32; 3],
+    pub blocks: Vec<([i32; 3], String)>,
+}
+
+// "minecraft:oak_stairs[facing=north]" -> "oak_stairs"
+fn block_name(state: &str) -> String {
+    let name = state.split('[').next().unwrap_or(state);
+    name.strip_prefix("minecraft:").unwrap_or(name).to_string()
+}
+
+fn is_air(name: &str) -> bool {
+    matches!(name, "air" | "cave_air" | "void_air" | "structure_void")
+}
+
+// archivo de estructura (.nbt): size, palette (o varias en palettes) y blocks con pos y state
+fn read_structure(root: &Tag) -> Result<Structure, Box<dyn Error>> {
+    let size = root.get("size").and_then(Tag::as_position).ok_or("estructura sin size")?;
+    let palette = root.get("palette")
+        .or_else(|| root.get("palettes").and_then(Tag::as_list).and_then(|palettes| palettes.first()))
+        .and_then(Tag::as_list)
+        .ok_or("estructura sin palette")?;
+    let names: Vec<String> = palette.iter()
+        .map(|entry| entry.get("Name").and_then(Tag::as_str).map(block_name).ok_or("entrada de palette sin Name"))
+        .collect::<Result<_, _>>()?;
+
+    let mut blocks = Vec::new();
+    for block in root.get("blocks").and_then(Tag::as_list).ok_or("estructura sin blocks")? {
+        let position = block.get("pos").and_then(Tag::as_position).ok_or("bloque sin pos")?;
+        let state = block.get("state").and_then(Tag::as_int).ok_or("bloque sin state")?;
+        let name = names.get(state as usize).ok_or("state fuera de la palette")?;
+        if !is_air(name) {
+            blocks.push((position, name.clone()));
+        }
+    }
+    Ok(Structure { size, blocks })
+}
+
+// schematic de Sponge (.schem), versión 2 o 3: Width/Height/Length, una palette de nombre -> índice
+// y los índices como varints en orden x, z, y
+fn read_schematic(root: &Tag) -> Result<Structure, Box<dyn Error>> {
+    // en la versión 3 todo está dentro de "Schematic" y los bloques en "Blocks"
+    let root = root.get("Schematic").unwrap_or(root);
+    let dimension = |name| root.get(name).and_then(Tag::as_int).map(|v| v as u16 as i32).ok_or(format!("schematic sin {}", name));
+    let size = [dimension("Width")?, dimension("Height")?, dimension("Length")?];
+    if size.contains(&0) {
+        return Err(format!("schematic con una dimensión en 0 ({}x{}x{})", size[0], size[1], size[2]).into());
+    }
+    let volume = size.iter().map(|&v| v as i64).product::<i64>();
+    let container = root.get("Blocks").unwrap_or(root);
+    let palette = container.get("Palette").and_then(Tag::as_compound).ok_or("schematic sin Palette")?;
+    let data = container.get("BlockData").or_else(|| container.get("Data")).and_then(Tag::as_bytes).ok_or("schematic sin datos de bloques")?;
+
+    let mut names = vec![String::new(); palette.len()];
+    for (state, index) in palette {
+        let index = index.as_int().ok_or("índice de Palette inválido")? as usize;
+        *names.get_mut(index).ok_or("índice de Palette fuera de rango")? = block_name(state);
+    }
+
+    let mut blocks = Vec::new();
+    let (mut index, mut value, mut shift) = (0i64, 0usize, 0);
+    for &byte in data {
+        value |= ((byte & 0x7f) as usize) << shift;
+        if byte & 0x80 != 0 {
+            shift += 7;
+            // un varint de 32 bits tiene a lo sumo 5 bytes
+            if shift > 28 {
+                return Err("varint demasiado largo en los datos de bloques".into());
+            }
+            continue;
+        }
+        if index >= volume {
+            return Err(format!("más bloques que los {} del schematic", volume).into());
+        }
+        let name = names.get(value).ok_or("bloque fuera de la Palette")?;
+        let (width, length) = (size[0] as i64, size[2] as i64);
+        let (x, z, y) = (index % width, (index / width) % length, index / (width * length));
+        if !is_air(name) {
+            blocks.push(([x as i32, y as i32, z as i32], name.clone()));
+        }
+        index += 1;
+        value = 0;
+        shift = 0;
+    }
+    if shift != 0 || index != volume {
+        return Err(format!("el schematic trae {} bloques y debería tener {}", index, volume).into());
+    }
+    Ok(Structure { size, blocks })
+}
+
+// lee .nbt o .schem, comprimidos con gzip o no; el tipo sale del contenido
+pub fn load(path: &str) -> Result<Structure, Box<dyn Error>> {
+    let root = nbt::parse(&inflate::gunzip(&std::fs::read(path)?)?)?;
+    if root.get("blocks").is_some() {
+        read_structure(&root)
+    } else {
+        read_schematic(&root)
+    }
+}
+
+// de qué bloque de este proyecto es cada id de Minecraft. las reglas pueden terminar o empezar
+// con * ("*_planks"); gana la primera que coincide y lo que no coincide con ninguna va como `fallback`
+pub struct BlockMapping {
+    rules: Vec<(String, String)>,
+    pub fallback: String,
+}
+
+impl Default for BlockMapping {
+    fn default() -> Self {
+        let rules = [
+            ("grass_block", "grass_block"), ("dirt", "dirt"), ("coarse_dirt", "dirt"), ("podzol", "dirt"),
+            ("rooted_dirt", "dirt"), ("farmland", "dirt"), ("dirt_path", "dirt"),
+            ("stone", "stone"), ("cobblestone", "stone"), ("granite", "stone"), ("diorite", "stone"),
+            ("andesite", "stone"), ("deepslate", "stone"), ("gravel", "stone"), ("smooth_stone", "stone"),
+            ("*stone_bricks", "stone_bricks"),
+            ("sand", "sand"), ("red_sand", "sand"), ("*sandstone", "sand"),
+            ("obsidian", "obsidian"), ("crying_obsidian", "obsidian"),
+            ("*_planks", "oak_planks"), ("*_fence", "oak_planks"), ("*_door", "oak_planks"),
+            ("birch_log", "birch_log"), ("birch_wood", "birch_log"), ("*_log", "oak_log"), ("*_wood", "oak_log"),
+            ("spruce_leaves", "spruce_leaves"), ("*_leaves", "oak_leaves"), ("cactus", "cactus"),
+            ("amethyst_block", "crystal"), ("*glass", "glass"), ("*glass_pane", "glass"),
+            ("iron_block", "iron_block"),
+            ("coal_ore", "coal_ore"), ("iron_ore", "iron_ore"), ("gold_ore", "gold_ore"),
+            ("redstone_ore", "redstone_ore"), ("diamond_ore", "diamond_ore"),
+            ("deepslate_coal_ore", "coal_ore"), ("deepslate_iron_ore", "iron_ore"), ("deepslate_gold_ore", "gold_ore"),
+            ("deepslate_redstone_ore", "redstone_ore"), ("deepslate_diamond_ore", "diamond_ore"),
+            ("lava", "lava"), ("water", "water"),
+            ("poppy", "poppy"), ("dandelion", "dandelion"), ("cornflower", "cornflower"), ("oxeye_daisy", "oxeye_daisy"),
+            ("white_wool", "color_e9ececff"), ("red_wool", "color_a12722ff"), ("yellow_wool", "color_f8c527ff"),
+            ("blue_wool", "color_35399dff"), ("black_wool", "color_141519ff"), ("bricks", "color_96614fff"),
+        ];
+        BlockMapping {
+            rules: rules.iter().map(|&(from, to)| (from.to_string(), to.to_string())).collect(),
+            fallback: "stone".to_string(),
+        }
+    }
+}
+
+impl BlockMapping {
+    // archivo de texto con "id = bloque" por línea (# comenta); van antes que las reglas de siempre
+    // y "* = bloque" cambia el de reemplazo
+    pub fn load(path: &str) -> Result<Self, Box<dyn Error>> {
+        let mut mapping = Self::default();
+        let mut rules = Vec::new();
+        for (number, line) in std::fs::read_to_string(path)?.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let (from, to) = line.split_once('=').ok_or(format!("línea {}: falta el =", number + 1))?;
+            let (from, to) = (block_name(from.trim()), to.trim().to_string());
+            if from == "*" {
+                mapping.fallback = to;
+            } else {
+                rules.push((from, to));
+            }
+        }
+        rules.append(&mut mapping.rules);
+        mapping.rules = rules;
+        Ok(mapping)
+    }
+
+    // el bloque de la primera regla que coincide, o nada si ninguna coincide
+    pub fn target(&self, name: &str) -> Option<&str> {
+        let matches = |pattern: &str| match (pattern.strip_prefix('*'), pattern.strip_suffix('*')) {
+            (Some(suffix), _) => name.ends_with(suffix),
+            (_, Some(prefix)) => name.starts_with(prefix),
+            _ => name == pattern,
+        };
+        self.rules.iter().find(|(pattern, _)| matches(pattern)).map(|(_, to)| to.as_str())
+    }
+}
+
+impl Structure {
+    // bloques con la esquina mínima en `offset`; devuelve también los ids de Minecraft que no tenían regla
+    pub fn placements(&self, mapping: &BlockMapping, registry: &mut BlockRegistry, offset: [i32; 3]) -> (Vec<Placement>, Vec<String>) {
+        let fallback = registry.resolve(&mapping.fallback).or_else(|| registry.id("stone")).unwrap_or(BlockId::AIR);
+        let mut unknown: Vec<String> = Vec::new();
+        let placements = self.blocks.iter().map(|([x, y, z], name)| {
+            // una regla que apunta a un bloque que no existe también cae en el de reemplazo
+            let id = mapping.target(name).and_then(|target| registry.resolve(target));
+            if id.is_none() && !unknown.contains(name) {
+                unknown.push(name.clone());
+            }
+            ([offset[0] + x, offset[1] + y, offset[2] + z], id.unwrap_or(fallback))
+        }).collect();
+        (placements, unknown)
+    }
+}
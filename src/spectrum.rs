@@ -0,0 +1,42 @@
+use nalgebra_glm::Vec3;
+
+pub const MIN_WAVELENGTH: f32 = 400.0;
+pub const MAX_WAVELENGTH: f32 = 700.0;
+
+// cuántas longitudes de onda se trazan cuando un rayo blanco entra a un material dispersivo
+pub const SPECTRAL_SAMPLES: usize = 6;
+
+// longitud de onda en el centro de cada franja del espectro visible
+pub fn sample_wavelength(index: usize) -> f32 {
+    let step = (MAX_WAVELENGTH - MIN_WAVELENGTH) / SPECTRAL_SAMPLES as f32;
+    MIN_WAVELENGTH + (index as f32 + 0.5) * step
+}
+
+// aproximación por tramos (Bruton) del color de una longitud de onda en nm
+pub fn wavelength_to_rgb(wavelength: f32) -> Vec3 {
+    let w = wavelength;
+    let (r, g, b) = if w < 440.0 {
+        (-(w - 440.0) / (440.0 - 380.0), 0.0, 1.0)
+    } else if w < 490.0 {
+        (0.0, (w - 440.0) / (490.0 - 440.0), 1.0)
+    } else if w < 510.0 {
+        (0.0, 1.0, -(w - 510.0) / (510.0 - 490.0))
+    } else if w < 580.0 {
+        ((w - 510.0) / (580.0 - 510.0), 1.0, 0.0)
+    } else if w < 645.0 {
+        (1.0, -(w - 645.0) / (645.0 - 580.0), 0.0)
+    } else {
+        (1.0, 0.0, 0.0)
+    };
+
+    // la vista pierde sensibilidad en los extremos
+    let falloff = if w < 420.0 {
+        0.3 + 0.7 * (w - 380.0) / (420.0 - 380.0)
+    } else if w > 645.0 {
+        0.3 + 0.7 * (780.0 - w) / (780.0 - 645.0)
+    } else {
+        1.0
+    };
+
+    Vec3::new(r, g, b) * falloff.clamp(0.0, 1.0)
+}
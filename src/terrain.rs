@@ -0,0 +1,479 @@
+use crate::rng::Rng;
+use crate::hydrology::{self, Waterfall};
+use crate::material::Material;
+use crate::noise::{Noise, to_unit};
+use crate::ores::OreVein;
+use std::f32::consts::PI;
+
+// parámetros del generador; con la misma semilla sale el mismo diorama
+#[derive(Debug, Clone)]
+pub struct TerrainConfig {
+    pub size: usize,              // columnas por lado
+    pub seed: u32,
+    pub base_height: usize,       // altura mínima de una columna
+    pub height_range: usize,      // cuánto pueden subir las colinas sobre la base
+    pub max_height: usize,        // bloques de alto del volumen (y = 0..max_height)
+    pub height_frequency: f32,    // escala del ruido de alturas (más chico = colinas más anchas)
+    pub biome_frequency: f32,     // escala de los mapas de temperatura y humedad
+    pub sea_level: usize,         // las columnas por debajo se llenan de fluido
+    pub overhang_strength: f32,   // cuánto deforma el ruido 3D la superficie (voladizos, arcos)
+    pub cave_threshold: f32,      // cuevas "queso": se vacía donde el ruido 3D pasa de esto
+    pub worm_count: usize,        // túneles "gusano"
+    pub cave_lava_level: usize,   // el piso de las cuevas volcánicas hasta esta altura lleva lava
+    pub lake_min_area: usize,     // pozos con menos columnas que esto se rellenan en vez de ser lagos
+    pub river_count: usize,       // ríos que nacen en las partes altas (más los que salen de los lagos)
+    pub waterfall_drop: usize,    // desnivel mínimo del cauce para que haya cascada (1 = también los escalones)
+    pub vegetation_density: f32,  // 0 = sin plantas, 1 = lo normal de cada bioma
+    pub ores: Vec<OreVein>,       // menas que reemplazan la piedra de relleno
+}
+
+impl Default for TerrainConfig {
+    fn default() -> Self {
+        TerrainConfig {
+            size: 18,
+            seed: 1337,
+            base_height: 2,
+            height_range: 4,
+            max_height: 12,
+            height_frequency: 0.12,
+            biome_frequency: 0.07,
+            sea_level: 3,
+            overhang_strength: 2.5,
+            cave_threshold: 0.3,
+            worm_count: 3,
+            cave_lava_level: 2,
+            lake_min_area: 3,
+            river_count: 2,
+            waterfall_drop: 1,
+            vegetation_density: 1.0,
+            ores: OreVein::defaults(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Biome {
+    Volcanic,
+    Desert,
+    Grassland,
+    Forest,
+}
+
+// qué bloques lleva cada capa de una columna del bioma, por su nombre en el registro
+pub struct BiomeRules {
+    pub surface: &'static str,
+    pub subsurface: &'static str,
+    pub subsurface_depth: usize,
+    pub filler: &'static str,
+    pub liquid: fn() -> Material,  // con qué se llenan los huecos bajo el nivel del mar
+    pub elevation: f32,            // bloques extra sobre la base (negativo = hondonada)
+    pub relief: f32,               // qué tanto del rango de alturas usa
+    pub overhang: f32,             // multiplica overhang_strength
+    pub cave_bias: f32,            // baja el umbral de las cuevas: más hueco
+}
+
+impl Biome {
+    // muy caliente = volcánico, caliente y seco = desierto, húmedo = bosque, lo demás pradera
+    pub fn from_climate(temperature: f32, humidity: f32) -> Self {
+        if temperature > 0.72 {
+            Biome::Volcanic
+        } else if temperature > 0.45 && humidity < 0.5 {
+            Biome::Desert
+        } else if humidity > 0.5 {
+            Biome::Forest
+        } else {
+            Biome::Grassland
+        }
+    }
+
+    pub fn rules(&self) -> BiomeRules {
+        match self {
+            Biome::Volcanic => BiomeRules {
+                surface: "stone",
+                subsurface: "obsidian",
+                subsurface_depth: 1,
+                filler: "stone",
+                liquid: Material::lava_surface,
+                elevation: 1.5,
+                relief: 1.0,
+                overhang: 1.0,
+                cave_bias: 0.2,
+            },
+            Biome::Desert => BiomeRules {
+                surface: "sand",
+                subsurface: "sand",
+                subsurface_depth: 2,
+                filler: "stone",
+                liquid: Material::water_surface,
+                elevation: -1.0,
+                relief: 0.6,
+                overhang: 0.3,
+                cave_bias: 0.0,
+            },
+            Biome::Grassland => BiomeRules {
+                surface: "grass_block",
+                subsurface: "dirt",
+                subsurface_depth: 1,
+                filler: "stone",
+                liquid: Material::water_surface,
+                elevation: 0.5,
+                relief: 0.6,
+                overhang: 0.4,
+                cave_bias: 0.0,
+            },
+            Biome::Forest => BiomeRules {
+                surface: "grass_block",
+                subsurface: "dirt",
+                subsurface_depth: 2,
+                filler: "stone",
+                liquid: Material::water_surface,
+                elevation: 1.0,
+                relief: 0.9,
+                overhang: 0.6,
+                cave_bias: 0.05,
+            },
+        }
+    }
+}
+
+// volumen de bloques, con biomas por columna; heights[z][x] es el bloque sólido más alto de la columna
+pub struct Terrain {
+    pub config: TerrainConfig,
+    pub heights: Vec<Vec<usize>>,
+    pub biomes: Vec<Vec<Biome>>,
+    solid: Vec<bool>,
+    ores: Vec<Option<&'static str>>,
+    water: Vec<Vec<Option<usize>>>,
+    waterfalls: Vec<Waterfall>,
+}
+
+impl Terrain {
+    pub fn generate(config: &TerrainConfig) -> Self {
+        let size = config.size;
+        let noise = Noise::new(config.seed);
+        let temperature_noise = noise.derived(1);
+        let humidity_noise = noise.derived(2);
+        let pit_noise = noise.derived(3);
+        let overhang_noise = noise.derived(4);
+        let cave_noise = noise.derived(5);
+
+        let mut biomes = vec![vec![Biome::Grassland; size]; size];
+        for (z, row) in biomes.iter_mut().enumerate() {
+            for (x, biome) in row.iter_mut().enumerate() {
+                let (bx, bz) = (x as f32 * config.biome_frequency, z as f32 * config.biome_frequency);
+                let temperature = to_unit(temperature_noise.simplex2(bx, bz) * 1.3);
+                let humidity = to_unit(humidity_noise.simplex2(bx + 31.7, bz - 12.4) * 1.3);
+                *biome = Biome::from_climate(temperature, humidity);
+            }
+        }
+
+        let mut terrain = Terrain {
+            config: config.clone(),
+            heights: vec![vec![0; size]; size],
+            biomes,
+            solid: vec![false; size * size * config.max_height],
+            ores: vec![None; size * size * config.max_height],
+            water: vec![vec![None; size]; size],
+            waterfalls: Vec::new(),
+        };
+
+        for z in 0..size {
+            for x in 0..size {
+                // se promedian las reglas de los vecinos para que no haya paredes en cada borde de bioma
+                let shape_rules = terrain.blended_rules(x, z, 1);
+                let rules = terrain.biome(x, z).rules();
+                let (hx, hz) = (x as f32 * config.height_frequency, z as f32 * config.height_frequency);
+                let shape = to_unit(noise.domain_warp2(hx, hz, 1.2, 4) * 2.4);
+                let mut surface = config.base_height as f32 + shape_rules.elevation
+                    + shape * shape_rules.relief * config.height_range as f32;
+
+                // pozos de lava: cerca del centro de las celdas de Worley la columna baja del nivel del mar
+                let is_pit = terrain.biome(x, z) == Biome::Volcanic
+                    && pit_noise.worley2(x as f32 * 0.35, z as f32 * 0.35).0 < 0.2;
+                if is_pit {
+                    surface = surface.min(config.sea_level as f32 - 1.0);
+                }
+                let surface = surface.round().max(1.0);
+
+                for y in 0..config.max_height {
+                    let (fx, fy, fz) = (x as f32, y as f32, z as f32);
+                    // densidad: positiva bajo la superficie, y el ruido 3D la empuja para formar voladizos
+                    let mut density = surface - fy + 0.5;
+                    if !is_pit {
+                        density += overhang_noise.fbm3(fx * 0.15, fy * 0.2, fz * 0.15, 3)
+                            * config.overhang_strength * shape_rules.overhang;
+                    }
+                    let mut solid = y == 0 || density > 0.0;
+
+                    // cuevas queso, siempre con al menos un bloque de techo
+                    if solid && y >= 1 && fy < surface - 1.0 {
+                        let cheese = cave_noise.fbm3(fx * 0.18, fy * 0.25, fz * 0.18, 3);
+                        if cheese > config.cave_threshold - rules.cave_bias {
+                            solid = false;
+                        }
+                    }
+                    terrain.set_solid(x, y, z, solid);
+                }
+            }
+        }
+
+        terrain.carve_worms(&noise.derived(6));
+        terrain.remove_floating_blocks();
+        terrain.update_heights();
+        terrain.apply_hydrology();
+        terrain.place_ores();
+        terrain
+    }
+
+    fn update_heights(&mut self) {
+        for z in 0..self.size() {
+            for x in 0..self.size() {
+                self.heights[z][x] = (0..self.config.max_height).rev().find(|&y| self.is_solid(x, y, z)).unwrap_or(0);
+            }
+        }
+    }
+
+    // lagos en los pozos, ríos cuesta abajo y cascadas; los pozos chicos se rellenan y los cauces se hunden un bloque
+    fn apply_hydrology(&mut self) {
+        let hydrology = hydrology::analyze(self);
+        for z in 0..self.size() {
+            for x in 0..self.size() {
+                let filled = hydrology.filled[z][x];
+                for y in self.height(x, z) + 1..=filled {
+                    self.set_solid(x, y, z, true);
+                }
+                if hydrology.rivers[z][x] && filled >= 1 {
+                    self.set_solid(x, filled, z, false);
+                    self.set_solid(x, filled - 1, z, true);
+                }
+            }
+        }
+        self.water = hydrology.water;
+        self.waterfalls = hydrology.waterfalls;
+        self.update_heights();
+    }
+
+    // las vetas solo quedan donde hay bloque sólido; block_name decide si se ven (solo en la capa de relleno)
+    fn place_ores(&mut self) {
+        let mut rng = Rng::new(self.config.seed ^ 0x0DE5_0DE5);
+        for ore in self.config.ores.clone() {
+            for (x, y, z) in ore.generate(self.size(), self.config.max_height, &mut rng) {
+                if self.solid_at(x, y, z) {
+                    let index = self.index(x as usize, y as usize, z as usize);
+                    self.ores[index] = Some(ore.block);
+                }
+            }
+        }
+    }
+
+    fn blended_rules(&self, x: usize, z: usize, radius: usize) -> BiomeRules {
+        let size = self.size();
+        let mut blended = self.biome(x, z).rules();
+        let (mut elevation, mut relief, mut overhang) = (0.0, 0.0, 0.0);
+        let mut count = 0.0;
+        for nz in z.saturating_sub(radius)..(z + radius + 1).min(size) {
+            for nx in x.saturating_sub(radius)..(x + radius + 1).min(size) {
+                let rules = self.biome(nx, nz).rules();
+                elevation += rules.elevation;
+                relief += rules.relief;
+                overhang += rules.overhang;
+                count += 1.0;
+            }
+        }
+        blended.elevation = elevation / count;
+        blended.relief = relief / count;
+        blended.overhang = overhang / count;
+        blended
+    }
+
+    // túneles gusano: caminatas que giran según Perlin 3D y vacían una esfera en cada paso;
+    // empiezan de preferencia en el bioma volcánico
+    fn carve_worms(&mut self, noise: &Noise) {
+        let size = self.size();
+        let mut rng = Rng::new(self.config.seed ^ 0x5EED_CAFE);
+        let volcanic: Vec<(usize, usize)> = (0..size * size)
+            .map(|i| (i % size, i / size))
+            .filter(|&(x, z)| self.biome(x, z) == Biome::Volcanic)
+            .collect();
+
+        for worm in 0..self.config.worm_count {
+            let (sx, sz) = if volcanic.is_empty() {
+                ((rng.next_f32() * size as f32) as usize, (rng.next_f32() * size as f32) as usize)
+            } else {
+                volcanic[((rng.next_f32() * volcanic.len() as f32) as usize).min(volcanic.len() - 1)]
+            };
+            let mut position = [sx as f32, 2.0 + rng.next_f32() * 2.0, sz as f32];
+            let mut yaw = rng.next_f32() * 2.0 * PI;
+            let steer = noise.derived(worm as u32);
+
+            for step in 0..40 {
+                let t = step as f32 * 0.15;
+                yaw += steer.perlin3(position[0] * 0.2, position[1] * 0.2, t) * 0.6;
+                let pitch = steer.derived(1).perlin3(position[0] * 0.2, t, position[2] * 0.2) * 0.35;
+                position[0] += yaw.cos() * pitch.cos() * 0.7;
+                position[1] = (position[1] + pitch.sin() * 0.7).clamp(1.5, self.config.max_height as f32 - 2.0);
+                position[2] += yaw.sin() * pitch.cos() * 0.7;
+
+                let radius = 1.0 + 0.4 * to_unit(steer.derived(2).perlin3(t, 0.0, 0.0));
+                self.carve_sphere(position, radius);
+            }
+        }
+    }
+
+    fn carve_sphere(&mut self, center: [f32; 3], radius: f32) {
+        let r = radius.ceil() as i32;
+        let (cx, cy, cz) = (center[0].round() as i32, center[1].round() as i32, center[2].round() as i32);
+        for dy in -r..=r {
+            for dz in -r..=r {
+                for dx in -r..=r {
+                    let (x, y, z) = (cx + dx, cy + dy, cz + dz);
+                    // el piso (y = 0) no se toca, para que no queden agujeros en el fondo
+                    if y < 1 || !self.in_bounds(x, y, z) {
+                        continue;
+                    }
+                    let d2 = (x as f32 - center[0]).powi(2) + (y as f32 - center[1]).powi(2) + (z as f32 - center[2]).powi(2);
+                    if d2 <= radius * radius {
+                        self.set_solid(x as usize, y as usize, z as usize, false);
+                    }
+                }
+            }
+        }
+    }
+
+    // los bloques sueltos que dejan el ruido y los túneles se ven como basura flotando
+    fn remove_floating_blocks(&mut self) {
+        let size = self.size();
+        let neighbors = [(1, 0, 0), (-1, 0, 0), (0, 1, 0), (0, -1, 0), (0, 0, 1), (0, 0, -1)];
+        for y in 1..self.config.max_height {
+            for z in 0..size {
+                for x in 0..size {
+                    if !self.is_solid(x, y, z) {
+                        continue;
+                    }
+                    let (xi, yi, zi) = (x as i32, y as i32, z as i32);
+                    let attached = neighbors.iter().any(|&(dx, dy, dz)| self.solid_at(xi + dx, yi + dy, zi + dz));
+                    if !attached {
+                        self.set_solid(x, y, z, false);
+                    }
+                }
+            }
+        }
+    }
+
+    fn in_bounds(&self, x: i32, y: i32, z: i32) -> bool {
+        let size = self.size() as i32;
+        x >= 0 && z >= 0 && y >= 0 && x < size && z < size && y < self.config.max_height as i32
+    }
+
+    fn index(&self, x: usize, y: usize, z: usize) -> usize {
+        (y * self.size() + z) * self.size() + x
+    }
+
+    fn set_solid(&mut self, x: usize, y: usize, z: usize, solid: bool) {
+        let index = self.index(x, y, z);
+        self.solid[index] = solid;
+    }
+
+    pub fn is_solid(&self, x: usize, y: usize, z: usize) -> bool {
+        y < self.config.max_height && self.solid[self.index(x, y, z)]
+    }
+
+    fn solid_at(&self, x: i32, y: i32, z: i32) -> bool {
+        self.in_bounds(x, y, z) && self.is_solid(x as usize, y as usize, z as usize)
+    }
+
+    pub fn size(&self) -> usize {
+        self.config.size
+    }
+
+    pub fn height(&self, x: usize, z: usize) -> usize {
+        self.heights[z][x]
+    }
+
+    pub fn biome(&self, x: usize, z: usize) -> Biome {
+        self.biomes[z][x]
+    }
+
+    // nivel del bloque de fluido más alto sobre la columna, y de qué es
+    pub fn fluid(&self, x: usize, z: usize) -> Option<(usize, Material)> {
+        if let Some(level) = self.water[z][x] {
+            return Some((level, Material::water_surface()));
+        }
+        let height = self.height(x, z);
+        if height >= self.config.sea_level {
+            return None;
+        }
+        Some((self.config.sea_level, (self.biome(x, z).rules().liquid)()))
+    }
+
+    pub fn waterfalls(&self) -> &[Waterfall] {
+        &self.waterfalls
+    }
+
+    // huecos sobre el piso de las cuevas volcánicas que se llenan de lava: (x, y, z)
+    pub fn cave_pools(&self) -> Vec<(usize, usize, usize)> {
+        let mut pools = Vec::new();
+        for z in 0..self.size() {
+            for x in 0..self.size() {
+                if self.biome(x, z) != Biome::Volcanic {
+                    continue;
+                }
+                for y in 1..=self.config.cave_lava_level.min(self.height(x, z)) {
+                    if !self.is_solid(x, y, z) && self.is_solid(x, y - 1, z) {
+                        pools.push((x, y, z));
+                    }
+                }
+            }
+        }
+        pools
+    }
+
+    // nombre en el registro del bloque sólido en (x, y, z)
+    pub fn block_name(&self, x: usize, z: usize, y_level: usize) -> &'static str {
+        let rules = self.biome(x, z).rules();
+        // cuántos bloques sólidos tiene encima antes de llegar al aire
+        let depth = (y_level + 1..self.config.max_height)
+            .take_while(|&y| self.is_solid(x, y, z))
+            .count();
+        // bajo un fluido no crece pasto: el fondo lleva la capa de abajo, igual que el piso de las cuevas
+        if y_level == self.height(x, z) && self.fluid(x, z).is_none() {
+            rules.surface
+        } else if depth <= rules.subsurface_depth {
+            rules.subsurface
+        } else {
+            self.ores[self.index(x, y_level, z)].unwrap_or(rules.filler)
+        }
+    }
+
+    // columnas secas del bioma, en orden de recorrido
+    pub fn dry_columns(&self, biome: Biome) -> Vec<(usize, usize)> {
+        let mut columns = Vec::new();
+        for z in 0..self.size() {
+            for x in 0..self.size() {
+                if self.biome(x, z) == biome && self.fluid(x, z).is_none() {
+                    columns.push((x, z));
+                }
+            }
+        }
+        columns
+    }
+
+    // piso seco dentro de las cuevas del bioma, con `clearance` bloques libres encima: (x, y, z) del primer hueco
+    pub fn cave_floors(&self, biome: Biome, clearance: usize) -> Vec<(usize, usize, usize)> {
+        let mut floors = Vec::new();
+        for z in 0..self.size() {
+            for x in 0..self.size() {
+                if self.biome(x, z) != biome {
+                    continue;
+                }
+                for y in self.config.cave_lava_level + 1..self.height(x, z) {
+                    let open = (y..y + clearance).all(|yy| yy < self.height(x, z) && !self.is_solid(x, yy, z));
+                    if open && self.is_solid(x, y - 1, z) {
+                        floors.push((x, y, z));
+                    }
+                }
+            }
+        }
+        floors
+    }
+}
@@ -0,0 +1,556 @@
+use crate::animated_texture::AnimatedTexture;
+use crate::color::Color;
+use crate::material::MaterialType;
+use crate::noise::{Noise, to_unit};
+use image::open;
+use nalgebra_glm::Vec3;
+use std::collections::HashMap;
+use std::f32::consts::TAU;
+
+#[derive(Clone)]
+pub struct Texture {
+    pub width: u32,
+    pub height: u32,
+    pub data: Vec<u8>,
+}
+
+impl Texture {
+    pub fn load_from_file(path: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let img = open(path)?;
+        let rgb_img = img.to_rgb8();
+        let (width, height) = rgb_img.dimensions();
+        let data = rgb_img.into_raw();
+        Ok(Texture { width, height, data })
+    }
+    
+    pub fn sample(&self, u: f32, v: f32) -> Color {
+        let u = (u.fract() + 1.0).fract().clamp(0.0, 1.0);
+        let v = (v.fract() + 1.0).fract().clamp(0.0, 1.0);
+        let x = ((u * self.width as f32) as u32).min(self.width - 1);
+        let y = ((v * self.height as f32) as u32).min(self.height - 1);
+        let index = ((y * self.width + x) * 3) as usize;
+        
+        if index + 2 < self.data.len() {
+            Color::new(self.data[index], self.data[index + 1], self.data[index + 2])
+        } else {
+            Color::new(255, 0, 255)
+        }
+    }
+    
+    pub fn create_grass_texture() -> Self {
+        let size = 32;
+        let noise = Noise::new(11);
+        let blades = noise.derived(1);
+        let mut data = Vec::with_capacity((size * size * 3) as usize);
+        for y in 0..size {
+            for x in 0..size {
+                let (u, v) = (x as f32 / 8.0, y as f32 / 8.0);
+                let clumps = to_unit(noise.fbm2_tiled(u, v, 4, 3) * 1.4);
+                let (f1, _) = blades.worley2_tiled(u * 2.0, v * 2.0, 8);
+                let combined_noise = (clumps * 0.8 + (1.0 - f1).max(0.0) * 0.3).clamp(0.0, 1.0);
+                let base_green = 160 + (combined_noise * 60.0) as u8;
+                let r = (25.0 + combined_noise * 35.0) as u8;
+                let b = (25.0 + combined_noise * 30.0) as u8;
+                data.extend_from_slice(&[r, base_green, b]);
+            }
+        }
+        Texture { width: 32, height: 32, data }
+    }
+
+    pub fn create_cactus_texture() -> Self {
+        let size = 32;
+        let mut data = Vec::with_capacity((size * size * 3) as usize);
+        for y in 0..size {
+            for x in 0..size {
+                let stripe = if (x / 4) % 2 == 0 { 18 } else { 28 };
+                let g = 140 + stripe;
+                let r = 40 + (stripe / 2);
+                let b = 40 + (stripe / 3);
+                data.extend_from_slice(&[r as u8, g as u8, b as u8]);
+            }
+        }
+        Texture { width: size, height: size, data }
+    }
+    
+    pub fn create_stone_texture() -> Self {
+        let size = 32;
+        let noise = Noise::new(23);
+        let mut data = Vec::with_capacity((size * size * 3) as usize);
+        for y in 0..size {
+            for x in 0..size {
+                let (u, v) = (x as f32 / 8.0, y as f32 / 8.0);
+                // vetas: fBm deformado, más las grietas entre celdas de Worley
+                let veins = to_unit(noise.domain_warp2_tiled(u, v, 4, 1.5, 4) * 1.5);
+                let (f1, f2) = noise.derived(1).worley2_tiled(u, v, 4);
+                let cracks = ((f2 - f1) * 4.0).min(1.0);
+                let combined_noise = (veins * 0.7 + cracks * 0.3).clamp(0.0, 1.0);
+                let base_gray = (70.0 + combined_noise * 40.0) as u8;
+                let variation = (combined_noise * 15.0) as u8;
+                data.extend_from_slice(&[ base_gray + variation, base_gray + (variation / 2), base_gray ]);
+            }
+        }
+        Texture { width: 32, height: 32, data }
+    }
+    
+    pub fn create_dirt_texture() -> Self {
+        let size = 32;
+        let noise = Noise::new(37);
+        let mut data = Vec::with_capacity((size * size * 3) as usize);
+        for y in 0..size {
+            for x in 0..size {
+                let (u, v) = (x as f32 / 8.0, y as f32 / 8.0);
+                let clods = to_unit(noise.fbm2_tiled(u, v, 4, 4) * 1.5);
+                // piedritas sueltas
+                let (f1, _) = noise.derived(1).worley2_tiled(u * 2.0, v * 2.0, 8);
+                let pebble = if f1 < 0.18 { 0.35 } else { 0.0 };
+                let combined_noise = (clods + pebble).clamp(0.0, 1.0);
+                let brown_r = (140.0 + combined_noise * 50.0) as u8;
+                let brown_g = (85.0 + combined_noise * 35.0) as u8;
+                let brown_b = (35.0 + combined_noise * 25.0) as u8;
+                data.extend_from_slice(&[brown_r, brown_g, brown_b]);
+            }
+        }
+        Texture { width: 32, height: 32, data }
+    }
+    
+     
+     // agua más intensa
+     pub fn create_water_texture() -> Self {
+        let size = 32;
+        let noise = Noise::new(41);
+        let mut data = Vec::with_capacity((size * size * 3) as usize);
+        for y in 0..size {
+            for x in 0..size {
+                
+                let (u, v) = (x as f32 / 8.0, y as f32 / 8.0);
+                let w = (noise.domain_warp2_tiled(u, v, 4, 1.0, 3) * 0.9).clamp(-0.6, 0.6);
+
+            
+                let r = (15.0 + w * 10.0).round().clamp(0.0, 40.0) as u8;
+                let g = (90.0 + w * 25.0).round().clamp(70.0, 140.0) as u8;
+                let b = (205.0 + w * 40.0).round().clamp(160.0, 255.0) as u8;
+
+                data.extend_from_slice(&[r, g, b]);
+            }
+        }
+        Texture { width: size, height: size, data }
+    }
+
+
+    
+    pub fn create_lava_texture() -> Self {
+        let size = 32;
+        let noise = Noise::new(53);
+        let mut data = Vec::with_capacity((size * size * 3) as usize);
+        for y in 0..size {
+            for x in 0..size {
+                let (u, v) = (x as f32 / 8.0, y as f32 / 8.0);
+                let intensity = to_unit(noise.domain_warp2_tiled(u, v, 4, 2.0, 4) * 1.6);
+                if intensity > 0.7 {
+                    data.extend_from_slice(&[255, 255, (150.0 + intensity * 105.0) as u8]);
+                } else if intensity > 0.4 {
+                    data.extend_from_slice(&[255, (120.0 + intensity * 135.0) as u8, 30]);
+                } else {
+                    data.extend_from_slice(&[(180.0 + intensity * 75.0) as u8, 20, 0]);
+                }
+            }
+        }
+        Texture { width: 32, height: 32, data }
+    }
+    
+    // lava que fluye: ruido que se desplaza con el tiempo y grietas que brillan
+    pub fn create_lava_frame(time: f32) -> Self {
+        let size = 64;
+        let noise = Noise::new(53);
+        let mut data = Vec::with_capacity((size * size * 3) as usize);
+        for y in 0..size {
+            for x in 0..size {
+                let u = x as f32 / size as f32 * 8.0;
+                let v = y as f32 / size as f32 * 8.0;
+
+                let flow = to_unit(noise.perlin2_tiled(u + time * 0.4, v + time * 0.2, 8)) * 0.6
+                    + to_unit(noise.derived(1).perlin2_tiled(u * 2.0 - time * 0.3, v * 2.0 + time * 0.5, 16)) * 0.4;
+
+                let crack = noise.derived(2).fbm2_tiled(u * 1.5 + time * 0.1, v * 1.5, 12, 2);
+                let ridge = (1.0 - crack.abs()).powi(8);
+                let pulse = 0.7 + 0.3 * (time * 3.0 + u).sin();
+                let glow = (ridge * pulse).clamp(0.0, 1.0);
+
+                let r = 140.0 + flow * 115.0;
+                let g = 25.0 + flow * 100.0 + glow * 130.0;
+                let b = glow * 150.0;
+                data.extend_from_slice(&[r.min(255.0) as u8, g.min(255.0) as u8, b.min(255.0) as u8]);
+            }
+        }
+        Texture { width: size, height: size, data }
+    }
+
+    // agua con ondas que avanzan; frecuencias enteras para que siga siendo repetible
+    pub fn create_water_frame(time: f32) -> Self {
+        let size = 64;
+        let tau = 2.0 * std::f32::consts::PI;
+        let noise = Noise::new(41);
+        let mut data = Vec::with_capacity((size * size * 3) as usize);
+        for y in 0..size {
+            for x in 0..size {
+                let u = x as f32 / size as f32;
+                let v = y as f32 / size as f32;
+
+                let w1 = ((u * 2.0 + v) * tau + time * 2.0).sin() * 0.3;
+                let w2 = ((v * 3.0 - u) * tau - time * 1.5).sin() * 0.25;
+                let w3 = noise.perlin2_tiled(u * 4.0 + time * 0.5, v * 4.0, 4) * 0.2;
+                let w = (w1 + w2 + w3).clamp(-0.6, 0.6);
+
+                let r = (15.0 + w * 10.0).round().clamp(0.0, 40.0) as u8;
+                let g = (90.0 + w * 25.0).round().clamp(70.0, 140.0) as u8;
+                let b = (205.0 + w * 40.0).round().clamp(160.0, 255.0) as u8;
+                data.extend_from_slice(&[r, g, b]);
+            }
+        }
+        Texture { width: size, height: size, data }
+    }
+
+    pub fn create_obsidian_texture() -> Self {
+        let size = 32;
+        let noise = Noise::new(67);
+        let mut data = Vec::with_capacity((size * size * 3) as usize);
+        for y in 0..size {
+            for x in 0..size {
+                let (u, v) = (x as f32 / 8.0, y as f32 / 8.0);
+                // vidrio volcánico: facetas de Worley con un poco de fBm encima
+                let (f1, f2) = noise.worley2_tiled(u, v, 4);
+                let facets = (f2 - f1).min(1.0);
+                let combined_noise = (facets * 0.8 + to_unit(noise.fbm2_tiled(u, v, 4, 3)) * 0.3).clamp(0.0, 1.0);
+                
+                let base_intensity = 15.0 + combined_noise * 25.0;
+                let purple_tint = if combined_noise > 0.8 { 20.0 } else { 5.0 };
+                
+                let r = (base_intensity + purple_tint * 0.6) as u8;
+                let g = base_intensity as u8;
+                let b = (base_intensity + purple_tint) as u8;
+                
+                data.extend_from_slice(&[r, g, b]);
+            }
+        }
+        Texture { width: 32, height: 32, data }
+    }
+
+    // new for tree
+    pub fn create_sand_texture() -> Self {
+        let size = 32;
+        let noise = Noise::new(79);
+        let mut data = Vec::with_capacity((size*size*3) as usize);
+        for y in 0..size {
+            for x in 0..size {
+                let (u, v) = (x as f32 / 8.0, y as f32 / 8.0);
+                // dunas suaves en una dirección más grano fino; las ondas dan vueltas enteras en el bloque
+                let warp = to_unit(noise.perlin2_tiled(u, v, 4));
+                let n1 = ((x as f32 / size as f32 * 2.0 + warp * 0.6) * TAU).sin() * 0.5 + 0.5;
+                let n2 = to_unit(noise.derived(1).fbm2_tiled(u * 2.0, v * 2.0, 8, 2) * 1.5);
+                let t = (0.6 + 0.4*(n1*0.7 + n2*0.3)).clamp(0.0,1.0);
+                let r = (210.0 + 40.0*t) as u8;
+                let g = (190.0 + 35.0*t) as u8;
+                let b = (140.0 + 25.0*t) as u8;
+                data.extend_from_slice(&[r,g,b]);
+            }
+        }
+        Texture { width: size, height: size, data }
+    }
+
+    pub fn create_wood_texture() -> Self {
+        let size = 32;
+        let noise = Noise::new(83);
+        let mut data = Vec::with_capacity((size*size*3) as usize);
+        for y in 0..size {
+            for x in 0..size {
+                // anillos deformados por ruido, para que no sean círculos perfectos
+                let warp = noise.fbm2_tiled(x as f32 / 8.0, y as f32 / 8.0, 4, 3) * 3.0;
+                // distancia al centro medida sobre un toro: cerca del centro es la de siempre y se repite en los bordes
+                let (a, b) = ((x as f32 - 16.0) / size as f32 * TAU, (y as f32 - 16.0) / size as f32 * TAU);
+                let radius = (2.0 * (2.0 - a.cos() - b.cos())).sqrt() * size as f32 / TAU;
+                let ring = ((radius * 0.4 + warp).sin()*0.5+0.5).clamp(0.0,1.0);
+                let r = (110.0 + 60.0*ring) as u8;
+                let g = (75.0 + 40.0*ring) as u8;
+                let b = (45.0 + 25.0*ring) as u8;
+                data.extend_from_slice(&[r,g,b]);
+            }
+        }
+        Texture { width: size, height: size, data }
+    }
+
+    pub fn create_leaves_texture() -> Self {
+        let size = 32;
+        let noise = Noise::new(97);
+        let mut data = Vec::with_capacity((size*size*3) as usize);
+        for y in 0..size {
+            for x in 0..size {
+                let (u, v) = (x as f32 / 4.0, y as f32 / 4.0);
+                // hojas como celdas: claras al centro, oscuras en los bordes
+                let (f1, f2) = noise.worley2_tiled(u, v, 8);
+                let n = ((f2 - f1) * 1.5).min(1.0) * 0.7 + to_unit(noise.perlin2_tiled(u, v, 8)) * 0.3;
+                let r = (30.0 + 40.0*n) as u8;
+                let g = (120.0 + 100.0*n) as u8;
+                let b = (30.0 + 35.0*n) as u8;
+                data.extend_from_slice(&[r,g,b]);
+            }
+        }
+        Texture { width: size, height: size, data }
+    }
+
+    pub fn create_birch_log_texture() -> Self {
+        let size = 32;
+        let noise = Noise::new(101);
+        let mut data = Vec::with_capacity((size*size*3) as usize);
+        for y in 0..size {
+            for x in 0..size {
+                let (u, v) = (x as f32 / 8.0, y as f32 / 8.0);
+                // rayas horizontales cortas: ruido estirado en x
+                let streak = noise.perlin2_tiled(u, v * 4.0, 4);
+                let bark = to_unit(noise.derived(1).fbm2_tiled(u, v, 4, 3));
+                let (r, g, b) = if streak > 0.55 {
+                    (45.0 + 20.0*bark, 40.0 + 15.0*bark, 35.0 + 15.0*bark)
+                } else {
+                    (200.0 + 40.0*bark, 196.0 + 38.0*bark, 180.0 + 35.0*bark)
+                };
+                data.extend_from_slice(&[r as u8, g as u8, b as u8]);
+            }
+        }
+        Texture { width: size, height: size, data }
+    }
+
+    pub fn create_spruce_leaves_texture() -> Self {
+        let size = 32;
+        let noise = Noise::new(103);
+        let mut data = Vec::with_capacity((size*size*3) as usize);
+        for y in 0..size {
+            for x in 0..size {
+                let (u, v) = (x as f32 / 4.0, y as f32 / 4.0);
+                // agujas: celdas más chicas y oscuras que las hojas normales
+                let (f1, f2) = noise.worley2_tiled(u * 1.5, v * 1.5, 12);
+                let n = ((f2 - f1) * 2.0).min(1.0) * 0.8 + to_unit(noise.perlin2_tiled(u, v, 8)) * 0.2;
+                let r = (20.0 + 30.0*n) as u8;
+                let g = (60.0 + 70.0*n) as u8;
+                let b = (35.0 + 40.0*n) as u8;
+                data.extend_from_slice(&[r,g,b]);
+            }
+        }
+        Texture { width: size, height: size, data }
+    }
+
+    // mena: la piedra de base con manchas del mineral en grupitos de Worley
+    pub fn ore_over(stone: &Texture, mineral: [u8; 3], seed: u32) -> Self {
+        let noise = Noise::new(seed);
+        let mut data = stone.data.clone();
+        for y in 0..stone.height {
+            for x in 0..stone.width {
+                let u = x as f32 / stone.width as f32 * 4.0;
+                let v = y as f32 / stone.height as f32 * 4.0;
+                let (f1, _) = noise.worley2_tiled(u, v, 4);
+                let grain = to_unit(noise.derived(1).perlin2_tiled(u * 4.0, v * 4.0, 16));
+                if f1 < 0.22 + 0.1 * grain {
+                    // más claro al centro de cada mancha
+                    let shade = 1.15 - f1 * 1.5;
+                    let index = ((y * stone.width + x) * 3) as usize;
+                    for c in 0..3 {
+                        data[index + c] = (mineral[c] as f32 * shade).min(255.0) as u8;
+                    }
+                }
+            }
+        }
+        Texture { width: stone.width, height: stone.height, data }
+    }
+
+    fn luminance_at(&self, x: i64, y: i64) -> f32 {
+        // envuelve para que el mapa sea continuo entre bloques
+        let x = x.rem_euclid(self.width as i64) as u32;
+        let y = y.rem_euclid(self.height as i64) as u32;
+        let index = ((y * self.width + x) * 3) as usize;
+        if index + 2 < self.data.len() {
+            (0.299 * self.data[index] as f32 + 0.587 * self.data[index + 1] as f32 + 0.114 * self.data[index + 2] as f32) / 255.0
+        } else {
+            0.0
+        }
+    }
+
+    // mapa de altura en gris a partir del brillo de la textura
+    pub fn height_map_from_luminance(&self) -> Self {
+        let mut data = Vec::with_capacity((self.width * self.height * 3) as usize);
+        for y in 0..self.height as i64 {
+            for x in 0..self.width as i64 {
+                let h = (self.luminance_at(x, y) * 255.0) as u8;
+                data.extend_from_slice(&[h, h, h]);
+            }
+        }
+        Texture { width: self.width, height: self.height, data }
+    }
+
+    // normal map en tangent space (convención OpenGL, verde = arriba) con Sobel sobre la altura
+    pub fn normal_map_from_height(height: &Texture, strength: f32) -> Self {
+        let mut data = Vec::with_capacity((height.width * height.height * 3) as usize);
+        // Sobel mide en pixeles, se escala para que el relieve no dependa de la resolución
+        let scale = strength * height.width.max(height.height) as f32 / 32.0;
+        for y in 0..height.height as i64 {
+            for x in 0..height.width as i64 {
+                let h = |dx: i64, dy: i64| height.luminance_at(x + dx, y + dy);
+                let dh_du = (h(1, -1) + 2.0 * h(1, 0) + h(1, 1)) - (h(-1, -1) + 2.0 * h(-1, 0) + h(-1, 1));
+                let dh_dv = (h(-1, 1) + 2.0 * h(0, 1) + h(1, 1)) - (h(-1, -1) + 2.0 * h(0, -1) + h(1, -1));
+                let n = nalgebra_glm::normalize(&Vec3::new(-dh_du * scale / 8.0, dh_dv * scale / 8.0, 1.0));
+                data.extend_from_slice(&[
+                    ((n.x * 0.5 + 0.5) * 255.0) as u8,
+                    ((n.y * 0.5 + 0.5) * 255.0) as u8,
+                    ((n.z * 0.5 + 0.5) * 255.0) as u8,
+                ]);
+            }
+        }
+        Texture { width: height.width, height: height.height, data }
+    }
+
+    pub fn sample_normal(&self, u: f32, v: f32) -> Vec3 {
+        let c = self.sample(u, v);
+        Vec3::new(
+            c.r as f32 / 127.5 - 1.0,
+            c.g as f32 / 127.5 - 1.0,
+            c.b as f32 / 127.5 - 1.0,
+        )
+    }
+
+    pub fn sample_height(&self, u: f32, v: f32) -> f32 {
+        self.sample(u, v).r as f32 / 255.0
+    }
+}
+
+// frames y segundos por frame de la animación que se arma con un PNG fijo (una vuelta en 3.2 s)
+const STILL_FRAMES: usize = 32;
+const STILL_FRAME_TIME: f32 = 0.1;
+
+// todas las texturas de los bloques, por tipo de material
+pub struct BlockTextures {
+    pub albedo: HashMap<MaterialType, Texture>,
+    pub normal_maps: HashMap<MaterialType, Texture>,
+    pub height_maps: HashMap<MaterialType, Texture>,
+    pub animations: Vec<(MaterialType, AnimatedTexture)>,
+}
+
+impl BlockTextures {
+    // `still_fluids` deja la lava y el agua quietas (con su PNG fijo si hay)
+    pub fn load(still_fluids: bool) -> Self {
+        let mut albedo = HashMap::new();
+
+        albedo.insert(MaterialType::Grass, Self::load_or("grass.png", Texture::create_grass_texture));
+        albedo.insert(MaterialType::Dirt, Self::load_or("dirt.png", Texture::create_dirt_texture));
+        albedo.insert(MaterialType::Stone, Self::load_or("stone.png", Texture::create_stone_texture));
+        albedo.insert(MaterialType::Water, Self::load_or("water.png", Texture::create_water_texture));
+        albedo.insert(MaterialType::Lava, Self::load_or("lava.png", Texture::create_lava_texture));
+        albedo.insert(MaterialType::Obsidian, Self::load_or("obsidian.png", Texture::create_obsidian_texture));
+        albedo.insert(MaterialType::Leaves, Self::load_or("leaves.png", Texture::create_leaves_texture));
+        albedo.insert(MaterialType::Wood, Self::load_or("wood.png", Texture::create_wood_texture));
+        albedo.insert(MaterialType::Cactus, Self::load_or("cactus.png", Texture::create_cactus_texture));
+        albedo.insert(MaterialType::Crystal, Self::load_or("crystal.png", || {
+            // fallback muy simple si no hay PNG
+            let mut t = Texture { width: 32, height: 32, data: vec![] };
+            for _ in 0..(32*32) { t.data.extend_from_slice(&[170, 210, 255]); }
+            t
+        }));
+        albedo.insert(MaterialType::Sand, Texture::create_sand_texture());
+        let stone = albedo[&MaterialType::Stone].clone();
+        for (material_type, path, mineral, seed) in [
+            (MaterialType::CoalOre, "coal_ore.png", [28, 28, 30], 211),
+            (MaterialType::IronOre, "iron_ore.png", [215, 170, 140], 223),
+            (MaterialType::GoldOre, "gold_ore.png", [250, 215, 60], 227),
+            (MaterialType::RedstoneOre, "redstone_ore.png", [220, 20, 15], 229),
+            (MaterialType::DiamondOre, "diamond_ore.png", [95, 235, 225], 233),
+        ] {
+            let texture = Texture::load_from_file(path).unwrap_or_else(|_| Texture::ore_over(&stone, mineral, seed));
+            albedo.insert(material_type, texture);
+        }
+        albedo.insert(MaterialType::BirchLog, Self::load_or("birch_log.png", Texture::create_birch_log_texture));
+        albedo.insert(MaterialType::SpruceLeaves, Self::load_or("spruce_leaves.png", Texture::create_spruce_leaves_texture));
+
+        let mut textures = BlockTextures {
+            albedo,
+            normal_maps: HashMap::new(),
+            height_maps: HashMap::new(),
+            animations: Vec::new(),
+        };
+
+        // lava y agua animadas: una tira tipo Minecraft, si no frames sacados del PNG fijo y si no la procedural
+        if !still_fluids {
+            textures.add_animation(MaterialType::Lava, "lava_still.png", "lava.png", Texture::create_lava_frame);
+            textures.add_animation(MaterialType::Water, "water_still.png", "water.png", Texture::create_water_frame);
+        }
+
+        // relieve para piedra, obsidiana y madera
+        textures.add_surface_maps(MaterialType::Stone, "stone_normal.png", "stone_height.png", 2.0);
+        textures.add_surface_maps(MaterialType::Obsidian, "obsidian_normal.png", "obsidian_height.png", 1.2);
+        textures.add_surface_maps(MaterialType::Wood, "wood_normal.png", "wood_height.png", 1.5);
+        // las manchas de las menas sobresalen un poco de la piedra
+        for (ore, name) in [
+            (MaterialType::CoalOre, "coal_ore"),
+            (MaterialType::IronOre, "iron_ore"),
+            (MaterialType::GoldOre, "gold_ore"),
+            (MaterialType::RedstoneOre, "redstone_ore"),
+            (MaterialType::DiamondOre, "diamond_ore"),
+        ] {
+            textures.add_surface_maps(ore, &format!("{}_normal.png", name), &format!("{}_height.png", name), 2.0);
+        }
+
+        textures
+    }
+
+    fn add_animation(&mut self, material_type: MaterialType, strip_path: &str, static_path: &str, generator: fn(f32) -> Texture) {
+        let animation = match AnimatedTexture::load_strip(strip_path) {
+            Ok(strip) => strip,
+            Err(_) => match Texture::load_from_file(static_path) {
+                Ok(still) => AnimatedTexture::from_still(&still, STILL_FRAMES, STILL_FRAME_TIME),
+                Err(_) => AnimatedTexture::procedural(generator),
+            },
+        };
+        self.animations.push((material_type, animation));
+        self.update(0.0);
+    }
+
+    // avanza las texturas animadas al tiempo dado (segundos desde que arrancó el visor)
+    pub fn update(&mut self, time: f32) {
+        for (material_type, animation) in self.animations.iter_mut() {
+            if let Some(frame) = animation.advance(time) {
+                self.albedo.insert(*material_type, frame);
+            }
+        }
+    }
+
+    fn load_or(path: &str, fallback: fn() -> Texture) -> Texture {
+        match Texture::load_from_file(path) {
+            Ok(tex) => tex,
+            Err(_) => fallback(),
+        }
+    }
+
+    // usa los PNG si existen, si no se sacan del brillo de la textura base
+    fn add_surface_maps(&mut self, material_type: MaterialType, normal_path: &str, height_path: &str, strength: f32) {
+        let Some(base) = self.albedo.get(&material_type) else { return; };
+
+        let height = match Texture::load_from_file(height_path) {
+            Ok(tex) => tex,
+            Err(_) => base.height_map_from_luminance(),
+        };
+        let normal = match Texture::load_from_file(normal_path) {
+            Ok(tex) => tex,
+            Err(_) => Texture::normal_map_from_height(&height, strength),
+        };
+
+        self.normal_maps.insert(material_type, normal);
+        self.height_maps.insert(material_type, height);
+    }
+
+    pub fn albedo(&self, material_type: MaterialType) -> Option<&Texture> {
+        self.albedo.get(&material_type)
+    }
+
+    pub fn normal_map(&self, material_type: MaterialType) -> Option<&Texture> {
+        self.normal_maps.get(&material_type)
+    }
+
+    pub fn height_map(&self, material_type: MaterialType) -> Option<&Texture> {
+        self.height_maps.get(&material_type)
+    }
+}
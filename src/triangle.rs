@@ -0,0 +1,216 @@
+use nalgebra_glm::{Vec3, cross, dot, normalize};
+use crate::material::Material;
+
+// triángulo de una malla importada, con normal y uv por vértice
+#[derive(Clone)]
+pub struct Triangle {
+    pub vertices: [Vec3; 3],
+    pub normals: [Vec3; 3],
+    pub uvs: [(f32, f32); 3],
+    pub material: Material,
+}
+
+impl Triangle {
+    // Möller–Trumbore; se ve de los dos lados
+    pub fn ray_intersect(&self, ray_origin: &Vec3, ray_direction: &Vec3) -> Option<f32> {
+        let [v0, v1, v2] = self.vertices;
+        let (edge1, edge2) = (v1 - v0, v2 - v0);
+        let p = cross(ray_direction, &edge2);
+        let det = dot(&edge1, &p);
+        if det.abs() < 1e-9 {
+            return None;
+        }
+        let inv_det = 1.0 / det;
+        let s = ray_origin - v0;
+        let u = dot(&s, &p) * inv_det;
+        if !(0.0..=1.0).contains(&u) {
+            return None;
+        }
+        let q = cross(&s, &edge1);
+        let v = dot(ray_direction, &q) * inv_det;
+        if v < 0.0 || u + v > 1.0 {
+            return None;
+        }
+        let t = dot(&edge2, &q) * inv_det;
+        if t > 0.001 { Some(t) } else { None }
+    }
+
+    // pesos de cada vértice para un punto sobre el triángulo
+    fn barycentric(&self, point: &Vec3) -> [f32; 3] {
+        let [v0, v1, v2] = self.vertices;
+        let (e0, e1, p) = (v1 - v0, v2 - v0, point - v0);
+        let (d00, d01, d11) = (dot(&e0, &e0), dot(&e0, &e1), dot(&e1, &e1));
+        let (d20, d21) = (dot(&p, &e0), dot(&p, &e1));
+        let denom = d00 * d11 - d01 * d01;
+        if denom.abs() < 1e-12 {
+            return [1.0, 0.0, 0.0];
+        }
+        let v = (d11 * d20 - d01 * d21) / denom;
+        let w = (d00 * d21 - d01 * d20) / denom;
+        [1.0 - v - w, v, w]
+    }
+
+    // normal geométrica, según el orden de los vértices (antihorario mirando de frente)
+    pub fn get_normal(&self) -> Vec3 {
+        let [v0, v1, v2] = self.vertices;
+        normalize(&cross(&(v1 - v0), &(v2 - v0)))
+    }
+
+    // normal suave: la de los vértices interpolada
+    pub fn get_smooth_normal(&self, point: &Vec3) -> Vec3 {
+        let [a, b, c] = self.barycentric(point);
+        normalize(&(self.normals[0] * a + self.normals[1] * b + self.normals[2] * c))
+    }
+
+    // uv interpolado; la textura se repite si se sale de 0..1
+    pub fn get_uv_coordinates(&self, point: &Vec3) -> (f32, f32) {
+        let [a, b, c] = self.barycentric(point);
+        let u = self.uvs[0].0 * a + self.uvs[1].0 * b + self.uvs[2].0 * c;
+        let v = self.uvs[0].1 * a + self.uvs[1].1 * b + self.uvs[2].1 * c;
+        (u.rem_euclid(1.0), v.rem_euclid(1.0))
+    }
+
+    // dP/du y dP/dv a partir de las diferencias de uv entre vértices
+    pub fn get_tangent_frame(&self) -> (Vec3, Vec3) {
+        let [v0, v1, v2] = self.vertices;
+        let (edge1, edge2) = (v1 - v0, v2 - v0);
+        let (du1, dv1) = (self.uvs[1].0 - self.uvs[0].0, self.uvs[1].1 - self.uvs[0].1);
+        let (du2, dv2) = (self.uvs[2].0 - self.uvs[0].0, self.uvs[2].1 - self.uvs[0].1);
+        let det = du1 * dv2 - du2 * dv1;
+        if det.abs() < 1e-9 {
+            // sin uv útiles: cualquier base sobre el plano
+            let normal = self.get_normal();
+            let helper = if normal.y.abs() < 0.9 { Vec3::new(0.0, 1.0, 0.0) } else { Vec3::new(1.0, 0.0, 0.0) };
+            let tangent = normalize(&cross(&helper, &normal));
+            return (tangent, cross(&normal, &tangent));
+        }
+        let tangent = (edge1 * dv2 - edge2 * dv1) / det;
+        let bitangent = (edge2 * du1 - edge1 * du2) / det;
+        (normalize(&tangent), normalize(&bitangent))
+    }
+
+    fn bounds(&self) -> (Vec3, Vec3) {
+        let [v0, v1, v2] = self.vertices;
+        (nalgebra_glm::min2(&nalgebra_glm::min2(&v0, &v1), &v2), nalgebra_glm::max2(&nalgebra_glm::max2(&v0, &v1), &v2))
+    }
+
+    fn centroid(&self) -> Vec3 {
+        (self.vertices[0] + self.vertices[1] + self.vertices[2]) / 3.0
+    }
+}
+
+// nodo del BVH: una hoja tiene `count` triángulos desde `start`; si no, el hijo izquierdo
+// es el nodo siguiente y el derecho está en `right`
+struct BvhNode {
+    min: Vec3,
+    max: Vec3,
+    start: usize,
+    count: usize,
+    right: usize,
+}
+
+const LEAF_TRIANGLES: usize = 4;
+
+// malla de triángulos con su BVH, para no probar todos los triángulos en cada rayo
+pub struct TriangleMesh {
+    pub triangles: Vec<Triangle>,
+    nodes: Vec<BvhNode>,
+}
+
+fn slab_distance(min: &Vec3, max: &Vec3, ray_origin: &Vec3, inv_direction: &Vec3) -> Option<f32> {
+    let mut t_min = 0.0f32;
+    let mut t_max = f32::INFINITY;
+    for i in 0..3 {
+        let t1 = (min[i] - ray_origin[i]) * inv_direction[i];
+        let t2 = (max[i] - ray_origin[i]) * inv_direction[i];
+        t_min = t_min.max(t1.min(t2));
+        t_max = t_max.min(t1.max(t2));
+    }
+    if t_min <= t_max { Some(t_min) } else { None }
+}
+
+impl TriangleMesh {
+    pub fn new(mut triangles: Vec<Triangle>) -> Self {
+        let mut nodes = Vec::new();
+        if !triangles.is_empty() {
+            let len = triangles.len();
+            Self::build(&mut triangles, &mut nodes, 0, len);
+        }
+        TriangleMesh { triangles, nodes }
+    }
+
+    // divide por la mitad en el eje más largo de los centros; los triángulos quedan ordenados por hoja
+    fn build(triangles: &mut [Triangle], nodes: &mut Vec<BvhNode>, start: usize, end: usize) -> usize {
+        let mut min = Vec3::new(f32::INFINITY, f32::INFINITY, f32::INFINITY);
+        let mut max = -min;
+        let mut centroid_min = min;
+        let mut centroid_max = max;
+        for triangle in &triangles[start..end] {
+            let (a, b) = triangle.bounds();
+            min = nalgebra_glm::min2(&min, &a);
+            max = nalgebra_glm::max2(&max, &b);
+            centroid_min = nalgebra_glm::min2(&centroid_min, &triangle.centroid());
+            centroid_max = nalgebra_glm::max2(&centroid_max, &triangle.centroid());
+        }
+        let index = nodes.len();
+        nodes.push(BvhNode { min, max, start, count: end - start, right: 0 });
+        if end - start <= LEAF_TRIANGLES {
+            return index;
+        }
+
+        let extent = centroid_max - centroid_min;
+        let axis = if extent.x >= extent.y && extent.x >= extent.z { 0 } else if extent.y >= extent.z { 1 } else { 2 };
+        let middle = (start + end) / 2;
+        triangles[start..end].select_nth_unstable_by(middle - start, |a, b| a.centroid()[axis].total_cmp(&b.centroid()[axis]));
+
+        nodes[index].count = 0;
+        Self::build(triangles, nodes, start, middle);
+        nodes[index].right = Self::build(triangles, nodes, middle, end);
+        index
+    }
+
+    pub fn bounds(&self) -> Option<(Vec3, Vec3)> {
+        self.nodes.first().map(|root| (root.min, root.max))
+    }
+
+    // la misma malla escalada y movida (la escala es igual en los tres ejes, las normales no cambian)
+    pub fn transformed(&self, scale: f32, translation: Vec3) -> TriangleMesh {
+        let triangles = self.triangles.iter().map(|triangle| Triangle {
+            vertices: triangle.vertices.map(|v| v * scale + translation),
+            ..triangle.clone()
+        }).collect();
+        TriangleMesh::new(triangles)
+    }
+
+    // triángulo más cercano (índice y distancia) antes de `max_distance`, entre los que acepta `keep`;
+    // los rechazados (cortados, transparentes para sombras) no frenan el recorrido
+    pub fn ray_intersect(&self, ray_origin: &Vec3, ray_direction: &Vec3, max_distance: f32,
+                         keep: impl Fn(&Triangle, f32) -> bool) -> Option<(usize, f32)> {
+        if self.nodes.is_empty() {
+            return None;
+        }
+        let inv_direction = Vec3::new(1.0 / ray_direction.x, 1.0 / ray_direction.y, 1.0 / ray_direction.z);
+        let mut closest: Option<(usize, f32)> = None;
+        let mut limit = max_distance;
+        let mut stack = vec![0];
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            if !slab_distance(&node.min, &node.max, ray_origin, &inv_direction).is_some_and(|t| t < limit) {
+                continue;
+            }
+            if node.count == 0 {
+                stack.push(node.right);
+                stack.push(index + 1);
+                continue;
+            }
+            for i in node.start..node.start + node.count {
+                if let Some(distance) = self.triangles[i].ray_intersect(ray_origin, ray_direction)
+                    && distance < limit && keep(&self.triangles[i], distance) {
+                    limit = distance;
+                    closest = Some((i, distance));
+                }
+            }
+        }
+        closest
+    }
+}
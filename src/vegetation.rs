@@ -0,0 +1,320 @@
+use crate::block::FLOWERS;
+use crate::rng::Rng;
+use crate::terrain::{Biome, Terrain};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TreeSpecies {
+    Oak,
+    Birch,
+    Spruce,
+    Jungle,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Plant {
+    Tree(TreeSpecies),
+    Cactus,
+    Bush,
+    Flower,
+}
+
+// un bloque de la planta, relativo al bloque de aire encima del suelo, con su nombre en el registro
+#[derive(Debug, Clone, Copy)]
+pub struct PlantBlock {
+    pub offset: (i32, i32, i32),
+    pub name: &'static str,
+}
+
+// planta ya ubicada: (x, y, z) es el primer bloque de aire sobre la columna
+pub struct PlacedPlant {
+    pub x: usize,
+    pub y: usize,
+    pub z: usize,
+    pub blocks: Vec<PlantBlock>,
+}
+
+// qué crece en cada bioma: (planta, peso) para árboles y para plantas chicas,
+// y la probabilidad base de que un punto del scatter reciba algo
+struct BiomeFlora {
+    trees: &'static [(Plant, f32)],
+    tree_chance: f32,
+    plants: &'static [(Plant, f32)],
+    plant_chance: f32,
+}
+
+fn flora(biome: Biome) -> BiomeFlora {
+    match biome {
+        Biome::Forest => BiomeFlora {
+            trees: &[
+                (Plant::Tree(TreeSpecies::Oak), 0.4),
+                (Plant::Tree(TreeSpecies::Birch), 0.25),
+                (Plant::Tree(TreeSpecies::Spruce), 0.25),
+                (Plant::Tree(TreeSpecies::Jungle), 0.1),
+            ],
+            tree_chance: 0.6,
+            plants: &[(Plant::Bush, 0.4), (Plant::Flower, 0.6)],
+            plant_chance: 0.5,
+        },
+        Biome::Grassland => BiomeFlora {
+            trees: &[(Plant::Tree(TreeSpecies::Oak), 0.7), (Plant::Tree(TreeSpecies::Birch), 0.3)],
+            tree_chance: 0.25,
+            plants: &[(Plant::Flower, 0.7), (Plant::Bush, 0.3)],
+            plant_chance: 0.6,
+        },
+        Biome::Desert => BiomeFlora {
+            trees: &[],
+            tree_chance: 0.0,
+            plants: &[(Plant::Cactus, 1.0)],
+            plant_chance: 0.35,
+        },
+        Biome::Volcanic => BiomeFlora {
+            trees: &[],
+            tree_chance: 0.0,
+            plants: &[],
+            plant_chance: 0.0,
+        },
+    }
+}
+
+fn pick(options: &[(Plant, f32)], rng: &mut Rng) -> Option<Plant> {
+    let total: f32 = options.iter().map(|(_, w)| w).sum();
+    let mut r = rng.next_f32() * total;
+    for &(plant, weight) in options {
+        if r < weight {
+            return Some(plant);
+        }
+        r -= weight;
+    }
+    options.last().map(|&(plant, _)| plant)
+}
+
+fn range(rng: &mut Rng, min: i32, max: i32) -> i32 {
+    min + ((rng.next_f32() * (max - min + 1) as f32) as i32).min(max - min)
+}
+
+impl Plant {
+    pub fn blocks(&self, rng: &mut Rng) -> Vec<PlantBlock> {
+        match self {
+            Plant::Tree(species) => tree_blocks(*species, rng),
+            Plant::Cactus => {
+                let height = range(rng, 1, 3);
+                (0..height).map(|y| block((0, y, 0), "cactus")).collect()
+            }
+            Plant::Bush => {
+                let mut blocks = vec![block((0, 0, 0), "oak_leaves")];
+                // a veces un segundo bloque al lado
+                if rng.next_f32() < 0.4 {
+                    let side = [(1, 0, 0), (-1, 0, 0), (0, 0, 1), (0, 0, -1)][range(rng, 0, 3) as usize];
+                    blocks.push(block(side, "oak_leaves"));
+                }
+                blocks
+            }
+            Plant::Flower => {
+                let (name, _) = FLOWERS[range(rng, 0, FLOWERS.len() as i32 - 1) as usize];
+                vec![block((0, 0, 0), name)]
+            }
+        }
+    }
+}
+
+fn block(offset: (i32, i32, i32), name: &'static str) -> PlantBlock {
+    PlantBlock { offset, name }
+}
+
+fn tree_blocks(species: TreeSpecies, rng: &mut Rng) -> Vec<PlantBlock> {
+    let mut blocks = Vec::new();
+    match species {
+        TreeSpecies::Oak | TreeSpecies::Birch => {
+            let (log, trunk) = if species == TreeSpecies::Oak {
+                ("oak_log", range(rng, 4, 5))
+            } else {
+                ("birch_log", range(rng, 5, 6))
+            };
+            for y in 0..trunk {
+                blocks.push(block((0, y, 0), log));
+            }
+            // copa estilo Minecraft: dos capas de 5x5 y dos de 3x3, esquinas al azar
+            for dy in -2..=1 {
+                let radius: i32 = if dy < 0 { 2 } else { 1 };
+                for dz in -radius..=radius {
+                    for dx in -radius..=radius {
+                        if dx == 0 && dz == 0 && dy < 0 {
+                            continue;  // ahí va el tronco
+                        }
+                        let corner = dx.abs() == radius && dz.abs() == radius;
+                        if corner && (dy == 1 || rng.next_f32() < 0.5) {
+                            continue;
+                        }
+                        blocks.push(block((dx, trunk + dy, dz), "oak_leaves"));
+                    }
+                }
+            }
+        }
+        TreeSpecies::Spruce => {
+            let trunk = range(rng, 6, 7);
+            for y in 0..trunk {
+                blocks.push(block((0, y, 0), "oak_log"));
+            }
+            // cono: capas de radio 2 y 1 alternadas, que se achican hacia la punta
+            for y in 2..=trunk {
+                let from_top = trunk - y;
+                let radius = if from_top == 0 { 0 } else if from_top % 2 == 1 { 1 } else { (from_top / 2).min(2) };
+                for dz in -radius..=radius {
+                    for dx in -radius..=radius {
+                        if (dx == 0 && dz == 0 && y < trunk) || (dx.abs() + dz.abs() > radius + 1) {
+                            continue;
+                        }
+                        blocks.push(block((dx, y, dz), "spruce_leaves"));
+                    }
+                }
+            }
+            blocks.push(block((0, trunk + 1, 0), "spruce_leaves"));
+        }
+        TreeSpecies::Jungle => {
+            // tronco de 2x2, alto, con una copa ancha y aplastada
+            let trunk = range(rng, 7, 9);
+            for y in 0..trunk {
+                for (dx, dz) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+                    blocks.push(block((dx, y, dz), "oak_log"));
+                }
+            }
+            for dy in -1..=1 {
+                let radius: i32 = if dy == 1 { 2 } else { 3 };
+                for dz in -radius..=radius + 1 {
+                    for dx in -radius..=radius + 1 {
+                        // distancia al centro del tronco de 2x2
+                        let (cx, cz) = (dx as f32 - 0.5, dz as f32 - 0.5);
+                        if cx * cx + cz * cz > (radius as f32 + 0.5).powi(2) {
+                            continue;
+                        }
+                        if dy < 1 && (0..=1).contains(&dx) && (0..=1).contains(&dz) {
+                            continue;
+                        }
+                        blocks.push(block((dx, trunk + dy, dz), "oak_leaves"));
+                    }
+                }
+            }
+        }
+    }
+    blocks
+}
+
+// muestreo de disco de Poisson (Bridson) en el cuadrado [0, size)²:
+// puntos al azar, ninguno a menos de `radius` de otro
+pub fn poisson_disc(size: f32, radius: f32, rng: &mut Rng) -> Vec<(f32, f32)> {
+    const ATTEMPTS: usize = 20;
+    let cell = radius / std::f32::consts::SQRT_2;
+    let cells = (size / cell).ceil() as usize;
+    let mut grid: Vec<Option<usize>> = vec![None; cells * cells];
+    let mut points = Vec::new();
+    let mut active = Vec::new();
+
+    let cell_of = |p: (f32, f32)| ((p.0 / cell) as usize).min(cells - 1) + ((p.1 / cell) as usize).min(cells - 1) * cells;
+
+    let first = (rng.next_f32() * size, rng.next_f32() * size);
+    grid[cell_of(first)] = Some(0);
+    points.push(first);
+    active.push(0);
+
+    while !active.is_empty() {
+        let slot = ((rng.next_f32() * active.len() as f32) as usize).min(active.len() - 1);
+        let origin = points[active[slot]];
+        let mut found = false;
+
+        for _ in 0..ATTEMPTS {
+            let angle = rng.next_f32() * 2.0 * std::f32::consts::PI;
+            let distance = radius * (1.0 + rng.next_f32());
+            let candidate = (origin.0 + angle.cos() * distance, origin.1 + angle.sin() * distance);
+            if candidate.0 < 0.0 || candidate.1 < 0.0 || candidate.0 >= size || candidate.1 >= size {
+                continue;
+            }
+
+            let (cx, cz) = ((candidate.0 / cell) as i32, (candidate.1 / cell) as i32);
+            let too_close = (-2..=2).any(|dz| (-2..=2).any(|dx| {
+                let (nx, nz) = (cx + dx, cz + dz);
+                if nx < 0 || nz < 0 || nx >= cells as i32 || nz >= cells as i32 {
+                    return false;
+                }
+                grid[nx as usize + nz as usize * cells].is_some_and(|i| {
+                    let p = points[i];
+                    (p.0 - candidate.0).powi(2) + (p.1 - candidate.1).powi(2) < radius * radius
+                })
+            }));
+
+            if !too_close {
+                grid[cell_of(candidate)] = Some(points.len());
+                active.push(points.len());
+                points.push(candidate);
+                found = true;
+                break;
+            }
+        }
+
+        if !found {
+            active.swap_remove(slot);
+        }
+    }
+    points
+}
+
+// cada planta necesita el suelo de su bioma
+fn grows_on(plant: Plant, ground: &str) -> bool {
+    match plant {
+        Plant::Cactus => ground == "sand",
+        _ => matches!(ground, "grass_block" | "dirt"),
+    }
+}
+
+// árboles primero, con más separación; después arbustos, flores y cactus en lo que queda libre.
+// `density` multiplica las probabilidades de cada bioma (0 = nada, 1 = normal)
+pub fn scatter(terrain: &Terrain, density: f32, seed: u32) -> Vec<PlacedPlant> {
+    let size = terrain.size();
+    let mut rng = Rng::new(seed ^ 0x7EED_5EED);
+    let mut occupied = vec![vec![false; size]; size];
+    let mut placed = Vec::new();
+
+    for (radius, trees) in [(3.5, true), (1.6, false)] {
+        for (px, pz) in poisson_disc(size as f32, radius, &mut rng) {
+            let (x, z) = (px as usize, pz as usize);
+            if occupied[z][x] || terrain.fluid(x, z).is_some() {
+                continue;
+            }
+            let flora = flora(terrain.biome(x, z));
+            let (options, chance) = if trees { (flora.trees, flora.tree_chance) } else { (flora.plants, flora.plant_chance) };
+            if rng.next_f32() >= chance * density {
+                continue;
+            }
+            let Some(plant) = pick(options, &mut rng) else { continue };
+
+            let y = terrain.height(x, z) + 1;
+            let ground = terrain.block_name(x, z, y - 1);
+            if !grows_on(plant, ground) {
+                continue;
+            }
+            // el tronco de 2x2 de la jungla necesita las cuatro columnas secas y a la misma altura
+            if plant == Plant::Tree(TreeSpecies::Jungle) {
+                let flat = [(1, 0), (0, 1), (1, 1)].iter().all(|&(dx, dz)| {
+                    let (nx, nz) = (x + dx, z + dz);
+                    nx < size && nz < size && !occupied[nz][nx]
+                        && terrain.fluid(nx, nz).is_none() && terrain.height(nx, nz) == y - 1
+                });
+                if !flat {
+                    continue;
+                }
+            }
+
+            let blocks = plant.blocks(&mut rng);
+            // el tronco de la jungla ocupa 2x2
+            for &PlantBlock { offset: (dx, dy, dz), .. } in &blocks {
+                if dy == 0 {
+                    let (bx, bz) = (x as i32 + dx, z as i32 + dz);
+                    if bx >= 0 && bz >= 0 && (bx as usize) < size && (bz as usize) < size {
+                        occupied[bz as usize][bx as usize] = true;
+                    }
+                }
+            }
+            placed.push(PlacedPlant { x, y, z, blocks });
+        }
+    }
+    placed
+}
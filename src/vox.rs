@@ -0,0 +1,124 @@
+use crate::block::BlockRegistry;
+use crate::region::Placement;
+use std::error::Error;
+
+// modelo de MagicaVoxel: tamaño y voxels (posición, índice en la paleta, 1 a 255).
+// MagicaVoxel usa z hacia arriba; acá ya están con y hacia arriba
+pub struct VoxModel {
+    pub size: [i32; 3],
+    pub voxels: Vec<([i32; 3], u8)>,
+}
+
+pub struct VoxFile {
+    pub models: Vec<VoxModel>,
+    pub palette: [[u8; 4]; 256],  // rgba por índice; el 0 no se usa
+}
+
+// la paleta que usa MagicaVoxel cuando el archivo no trae RGBA: un cubo de 6x6x6 colores
+// y rampas de rojo, verde, azul y gris
+fn default_palette() -> [[u8; 4]; 256] {
+    let mut palette = [[0; 4]; 256];
+    let cube = [0xff, 0xcc, 0x99, 0x66, 0x33, 0x00];
+    let ramp = [0xee, 0xdd, 0xbb, 0xaa, 0x88, 0x77, 0x55, 0x44, 0x22, 0x11];
+    let mut i = 1;
+    for r in cube {
+        for g in cube {
+            for b in cube {
+                // el negro del cubo no entra
+                if i < 216 {
+                    palette[i] = [r, g, b, 255];
+                    i += 1;
+                }
+            }
+        }
+    }
+    for channel in [[1, 0, 0], [0, 1, 0], [0, 0, 1], [1, 1, 1]] {
+        for v in ramp {
+            palette[i] = [v * channel[0], v * channel[1], v * channel[2], 255];
+            i += 1;
+        }
+    }
+    palette
+}
+
+fn read_i32(bytes: &[u8], position: usize) -> Result<i32, Box<dyn Error>> {
+    let slice = bytes.get(position..position + 4).ok_or("archivo .vox incompleto")?;
+    Ok(i32::from_le_bytes(slice.try_into().unwrap()))
+}
+
+// "VOX ", versión y un chunk MAIN con los demás adentro: SIZE + XYZI por modelo y RGBA.
+// el resto (escena, materiales, capas) se saltea
+pub fn load(path: &str) -> Result<VoxFile, Box<dyn Error>> {
+    let bytes = std::fs::read(path)?;
+    if bytes.get(0..4) != Some(b"VOX ") {
+        return Err("no es un archivo .vox".into());
+    }
+    if bytes.get(8..12) != Some(b"MAIN") {
+        return Err("al archivo .vox le falta el chunk MAIN".into());
+    }
+
+    let mut models = Vec::new();
+    let mut palette = default_palette();
+    let mut size = None;
+    // los hijos de MAIN arrancan después de su encabezado y su contenido; un tamaño negativo cuenta como 0
+    let mut position = 20 + read_i32(&bytes, 12)?.max(0) as usize;
+    while position + 12 <= bytes.len() {
+        let id = &bytes[position..position + 4];
+        let content_size = read_i32(&bytes, position + 4)?.max(0) as usize;
+        let children_size = read_i32(&bytes, position + 8)?.max(0) as usize;
+        let content = bytes.get(position + 12..position + 12 + content_size).ok_or("archivo .vox incompleto")?;
+        match id {
+            b"SIZE" => {
+                let (x, y, z) = (read_i32(content, 0)?, read_i32(content, 4)?, read_i32(content, 8)?);
+                size = Some([x, z, y]);
+            }
+            b"XYZI" => {
+                let size = size.take().ok_or("chunk XYZI sin SIZE antes")?;
+                let count = read_i32(content, 0)?.max(0) as usize;
+                let end = count.checked_mul(4).and_then(|n| n.checked_add(4)).ok_or("chunk XYZI incompleto")?;
+                let data = content.get(4..end).ok_or("chunk XYZI incompleto")?;
+                let voxels = data.chunks_exact(4)
+                    .filter(|v| v[3] != 0)
+                    .map(|v| ([v[0] as i32, v[2] as i32, size[2] - 1 - v[1] as i32], v[3]))
+                    .collect();
+                models.push(VoxModel { size, voxels });
+            }
+            b"RGBA" => {
+                // el color i del chunk es el índice i + 1
+                for (i, rgba) in content.chunks_exact(4).take(255).enumerate() {
+                    palette[i + 1] = [rgba[0], rgba[1], rgba[2], rgba[3]];
+                }
+            }
+            _ => {}
+        }
+        position = [12, content_size, children_size].into_iter()
+            .try_fold(position, usize::checked_add)
+            .ok_or("chunk de .vox demasiado grande")?;
+    }
+
+    if models.is_empty() {
+        return Err("el archivo .vox no tiene modelos".into());
+    }
+    Ok(VoxFile { models, palette })
+}
+
+impl VoxFile {
+    // bloques del modelo con la esquina mínima en `offset`, cada voxel como un cubo de `scale` bloques.
+    // cada color de la paleta que se usa pasa a ser un bloque liso de ese color
+    pub fn placements(&self, model: usize, registry: &mut BlockRegistry, offset: [i32; 3], scale: i32) -> Vec<Placement> {
+        let scale = scale.max(1);
+        let mut placements = Vec::new();
+        for &([x, y, z], index) in &self.models[model].voxels {
+            let id = registry.colored(self.palette[index as usize]);
+            for dy in 0..scale {
+                for dz in 0..scale {
+                    for dx in 0..scale {
+                        let position = [offset[0] + x * scale + dx, offset[1] + y * scale + dy, offset[2] + z * scale + dz];
+                        placements.push((position, id));
+                    }
+                }
+            }
+        }
+        placements
+    }
+}
@@ -0,0 +1,222 @@
+use crate::block::{BlockDef, BlockId, BlockRegistry};
+use nalgebra_glm::Vec3;
+use std::collections::BTreeMap;
+
+pub const CHUNK_SIZE: i32 = 16;
+pub const CHUNK_VOLUME: usize = (CHUNK_SIZE * CHUNK_SIZE * CHUNK_SIZE) as usize;
+
+// 16x16x16 bloques como índices a una paleta propia, empaquetados con los bits justos
+// (0 bits si todo el chunk es el mismo bloque); un índice nunca cruza de una palabra a otra
+#[derive(Clone)]
+pub struct Chunk {
+    palette: Vec<BlockId>,
+    bits: u32,
+    words: Vec<u64>,
+}
+
+fn bits_for(palette_len: usize) -> u32 {
+    if palette_len <= 1 { 0 } else { usize::BITS - (palette_len - 1).leading_zeros() }
+}
+
+impl Chunk {
+    pub fn filled(id: BlockId) -> Self {
+        Chunk { palette: vec![id], bits: 0, words: Vec::new() }
+    }
+
+    fn entry(&self, index: usize) -> usize {
+        if self.bits == 0 {
+            return 0;
+        }
+        let per_word = 64 / self.bits as usize;
+        let shift = (index % per_word) as u32 * self.bits;
+        ((self.words[index / per_word] >> shift) & ((1 << self.bits) - 1)) as usize
+    }
+
+    fn write_entry(&mut self, index: usize, entry: usize) {
+        let per_word = 64 / self.bits as usize;
+        let shift = (index % per_word) as u32 * self.bits;
+        let mask = ((1u64 << self.bits) - 1) << shift;
+        let word = &mut self.words[index / per_word];
+        *word = (*word & !mask) | ((entry as u64) << shift);
+    }
+
+    // cuando la paleta ya no entra en los bits que hay, se reescribe todo con uno más
+    fn repack(&mut self, bits: u32) {
+        let entries: Vec<usize> = (0..CHUNK_VOLUME).map(|i| self.entry(i)).collect();
+        self.bits = bits;
+        self.words = vec![0; CHUNK_VOLUME.div_ceil(64 / bits as usize)];
+        for (i, entry) in entries.into_iter().enumerate() {
+            self.write_entry(i, entry);
+        }
+    }
+
+    pub fn get(&self, index: usize) -> BlockId {
+        self.palette[self.entry(index)]
+    }
+
+    pub fn set(&mut self, index: usize, id: BlockId) {
+        let entry = match self.palette.iter().position(|&p| p == id) {
+            Some(entry) => entry,
+            None => {
+                self.palette.push(id);
+                let bits = bits_for(self.palette.len());
+                if bits > self.bits {
+                    self.repack(bits);
+                }
+                self.palette.len() - 1
+            }
+        };
+        if self.bits > 0 {
+            self.write_entry(index, entry);
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.palette.iter().all(|id| id.is_air())
+    }
+}
+
+// resultado de un raycast: el bloque tocado y la normal de la cara por la que entró el rayo
+#[derive(Debug, Clone, Copy)]
+pub struct RayHit {
+    pub block: [i32; 3],
+    pub normal: [i32; 3],
+    pub distance: f32,
+}
+
+impl RayHit {
+    // donde va un bloque puesto sobre la cara tocada
+    pub fn adjacent(&self) -> [i32; 3] {
+        [self.block[0] + self.normal[0], self.block[1] + self.normal[1], self.block[2] + self.normal[2]]
+    }
+}
+
+// mundo de bloques en chunks; lo que no tiene chunk es aire
+pub struct World {
+    pub registry: BlockRegistry,
+    chunks: BTreeMap<(i32, i32, i32), Chunk>,
+}
+
+impl World {
+    pub fn new(registry: BlockRegistry) -> Self {
+        World { registry, chunks: BTreeMap::new() }
+    }
+
+    pub fn chunk_key(x: i32, y: i32, z: i32) -> (i32, i32, i32) {
+        Self::locate(x, y, z).0
+    }
+
+    pub fn chunk_keys(&self) -> Vec<(i32, i32, i32)> {
+        self.chunks.keys().copied().collect()
+    }
+
+    pub fn chunk(&self, key: (i32, i32, i32)) -> Option<&Chunk> {
+        self.chunks.get(&key)
+    }
+
+    // cambia todos los bloques de una vez, como al cargar un archivo
+    pub fn replace_chunks(&mut self, chunks: BTreeMap<(i32, i32, i32), Chunk>) {
+        self.chunks = chunks;
+    }
+
+    // chunk que contiene el bloque y la posición del bloque dentro de él
+    fn locate(x: i32, y: i32, z: i32) -> ((i32, i32, i32), usize) {
+        let key = (x.div_euclid(CHUNK_SIZE), y.div_euclid(CHUNK_SIZE), z.div_euclid(CHUNK_SIZE));
+        let (lx, ly, lz) = (x.rem_euclid(CHUNK_SIZE), y.rem_euclid(CHUNK_SIZE), z.rem_euclid(CHUNK_SIZE));
+        (key, ((ly * CHUNK_SIZE + lz) * CHUNK_SIZE + lx) as usize)
+    }
+
+    pub fn get(&self, x: i32, y: i32, z: i32) -> BlockId {
+        let (key, index) = Self::locate(x, y, z);
+        self.chunks.get(&key).map_or(BlockId::AIR, |chunk| chunk.get(index))
+    }
+
+    pub fn block(&self, x: i32, y: i32, z: i32) -> &BlockDef {
+        self.registry.get(self.get(x, y, z))
+    }
+
+    pub fn set(&mut self, x: i32, y: i32, z: i32, id: BlockId) {
+        let (key, index) = Self::locate(x, y, z);
+        if id.is_air() && !self.chunks.contains_key(&key) {
+            return;
+        }
+        self.chunks.entry(key).or_insert_with(|| Chunk::filled(BlockId::AIR)).set(index, id);
+    }
+
+    // la generación pide los bloques por su nombre en el registro, que es lo que queda en los archivos
+    pub fn set_named(&mut self, x: i32, y: i32, z: i32, name: &str) {
+        let id = self.registry.id(name).unwrap_or_else(|| panic!("bloque sin registrar: {}", name));
+        self.set(x, y, z, id);
+    }
+
+    // todos los bloques que no son aire: (x, y, z, id)
+    pub fn blocks(&self) -> impl Iterator<Item = (i32, i32, i32, BlockId)> + '_ {
+        self.chunks.keys().flat_map(|&key| self.chunk_blocks(key))
+    }
+
+    // los bloques que no son aire de un chunk
+    pub fn chunk_blocks(&self, key: (i32, i32, i32)) -> impl Iterator<Item = (i32, i32, i32, BlockId)> + '_ {
+        let (cx, cy, cz) = key;
+        let chunk = self.chunks.get(&key).filter(|chunk| !chunk.is_empty());
+        chunk.into_iter().flat_map(move |chunk| {
+            (0..CHUNK_VOLUME).filter_map(move |i| {
+                let id = chunk.get(i);
+                let i = i as i32;
+                let (lx, lz, ly) = (i % CHUNK_SIZE, (i / CHUNK_SIZE) % CHUNK_SIZE, i / (CHUNK_SIZE * CHUNK_SIZE));
+                (!id.is_air()).then_some((cx * CHUNK_SIZE + lx, cy * CHUNK_SIZE + ly, cz * CHUNK_SIZE + lz, id))
+            })
+        })
+    }
+
+    // bloques del chunk con alguna cara a la vista; `keep` saca bloques (el corte) y lo que saca cuenta como aire
+    pub fn visible_blocks(&self, key: (i32, i32, i32), keep: impl Fn(i32, i32, i32) -> bool) -> Vec<(i32, i32, i32, BlockId)> {
+        let neighbors = [(1, 0, 0), (-1, 0, 0), (0, 1, 0), (0, -1, 0), (0, 0, 1), (0, 0, -1)];
+        self.chunk_blocks(key)
+            .filter(|&(x, y, z, _)| keep(x, y, z))
+            .filter(|&(x, y, z, _)| {
+                neighbors.iter().any(|&(dx, dy, dz)| {
+                    let (nx, ny, nz) = (x + dx, y + dy, z + dz);
+                    !keep(nx, ny, nz) || !self.block(nx, ny, nz).is_opaque()
+                })
+            })
+            .collect()
+    }
+
+    // recorre bloque por bloque lo que cruza el rayo (Amanatides-Woo) y devuelve el primero que no es aire.
+    // en coordenadas de bloque: el bloque (x, y, z) ocupa [x, x + 1)
+    pub fn raycast(&self, origin: Vec3, direction: Vec3, max_distance: f32, keep: impl Fn(i32, i32, i32) -> bool) -> Option<RayHit> {
+        let direction = nalgebra_glm::normalize(&direction);
+        let mut cell = [origin.x.floor() as i32, origin.y.floor() as i32, origin.z.floor() as i32];
+        let mut step = [0; 3];
+        let mut t_max = [f32::INFINITY; 3];
+        let mut t_delta = [f32::INFINITY; 3];
+        for i in 0..3 {
+            if direction[i] > 0.0 {
+                step[i] = 1;
+                t_max[i] = (cell[i] as f32 + 1.0 - origin[i]) / direction[i];
+                t_delta[i] = 1.0 / direction[i];
+            } else if direction[i] < 0.0 {
+                step[i] = -1;
+                t_max[i] = (cell[i] as f32 - origin[i]) / direction[i];
+                t_delta[i] = -1.0 / direction[i];
+            }
+        }
+
+        let mut normal = [0; 3];
+        let mut distance = 0.0;
+        while distance <= max_distance {
+            let (x, y, z) = (cell[0], cell[1], cell[2]);
+            if keep(x, y, z) && !self.get(x, y, z).is_air() {
+                return Some(RayHit { block: cell, normal, distance });
+            }
+            // avanza por el eje cuyo borde está más cerca
+            let axis = (0..3).min_by(|&a, &b| t_max[a].total_cmp(&t_max[b])).unwrap();
+            distance = t_max[axis];
+            cell[axis] += step[axis];
+            t_max[axis] += t_delta[axis];
+            normal = [0; 3];
+            normal[axis] = -step[axis];
+        }
+        None
+    }
+}